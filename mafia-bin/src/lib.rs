@@ -5,12 +5,14 @@ pub mod client;
 pub mod init;
 pub mod server;
 pub mod util;
+pub mod validate;
 
 use structopt::StructOpt;
 
 use crate::client::Client;
 use crate::init::init;
 use crate::server::Server;
+use crate::validate::validate;
 
 /// Rust implementation of the classic party game Mafia.
 #[derive(StructOpt)]
@@ -56,6 +58,13 @@ pub enum Command {
         seed: Option<u64>,
     },
 
+    /// Check a game directory's setup for mistakes.
+    Validate {
+        /// Storage directory.
+        #[structopt(long, parse(from_os_str), default_value = ".")]
+        path: std::path::PathBuf,
+    },
+
     /// Print version and exit.
     Version,
 }
@@ -103,6 +112,10 @@ pub async fn main(args: Vec<String>) -> Result<(), std::io::Error> {
             init(path, seed);
         }
 
+        Command::Validate { path } => {
+            validate(path)?;
+        }
+
         Command::Version => {
             println!("mafia {}", env!("CARGO_PKG_VERSION"));
         }
@@ -11,7 +11,9 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::prelude::*;
 use tokio::sync::RwLock;
 
-use mafia::{Action, Event, Game, Input, Map, Player, PlayerStatus, Visibility};
+use mafia::{Action, Event, Game, Input, Map, Phase, Player, PlayerStatus, RoleCard, Visibility};
+
+use crate::util::{load_file, load_setup};
 
 type Connections = Vec<Arc<RwLock<ConnState>>>;
 type KeyMap = Map<String, Visibility>;
@@ -74,6 +76,9 @@ pub enum Request {
     /// Immediately end the current phase (moderator only).
     EndPhase,
 
+    /// Send a chat message (player only).
+    Say(String),
+
     /// Use an action (player only).
     Use(Action),
 }
@@ -91,6 +96,12 @@ pub enum Response {
 
     /// Player status reminder.
     Players(Map<Player, PlayerStatus>),
+
+    /// A chat message, along with the name it was sent under.
+    Said(String, String),
+
+    /// Every player's role, sent once the game is over.
+    Summary(Map<Player, RoleCard>),
 }
 
 impl Server {
@@ -101,7 +112,7 @@ impl Server {
         let game = if game_path.exists() {
             load_file(&game_path)?
         } else if setup_path.exists() {
-            Game::new_from_state(load_file(&setup_path)?)
+            Game::new_from_state(load_setup(&setup_path)?)
         } else {
             return Err(io::Error::new(
                 io::ErrorKind::NotFound,
@@ -157,9 +168,15 @@ impl Server {
 
 impl ServerState {
     /// Update game state and send out events to clients.
-    async fn apply(self: &mut Self, input: &Input) -> Result<(), io::Error> {
+    ///
+    /// Returns the game's error if the input was rejected, in which case the
+    /// game state is unchanged and no events are sent.
+    async fn apply(self: &mut Self, input: &Input) -> Result<Result<(), mafia::Error>, io::Error> {
         // Update game state.
-        let log = self.game.apply(input).to_vec();
+        let log = match self.game.try_apply(input) {
+            Ok(log) => log.to_vec(),
+            Err(e) => return Ok(Err(e)),
+        };
         save_file(&self.path.join("game.ron"), &self.game);
 
         // Update clients and prune dead connections.
@@ -179,10 +196,66 @@ impl ServerState {
         }
         self.conns = new_conns;
 
+        Ok(Ok(()))
+    }
+
+    /// Send a chat message to everyone who can hear it.
+    ///
+    /// Living players talk in public during the day. At night the only channel
+    /// is the jail, where the jailor speaks as "Jailor" to keep their identity
+    /// hidden. Moderators hear everything.
+    async fn say(self: &mut Self, player: &Player, text: &str) -> Result<(), String> {
+        let (name, listeners) = chat_listeners(&self.game, player)?;
+
+        for conn in &self.conns {
+            let mut c = conn.write().await;
+            let hears = match (&c.auth, &listeners) {
+                (Visibility::Moderator, _) => true,
+                (_, None) => true,
+                (Visibility::Player(p), Some(listeners)) => listeners.contains(p),
+                (Visibility::Public, Some(_)) => false,
+            };
+            if hears {
+                if let Err(e) = c.send(Response::Said(name.clone(), text.to_string())).await {
+                    debug!("{} [{:?}]: <ERROR: {}>", c.peer, c.auth, e);
+                }
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Work out the name a player chats under and who can hear them, with None
+/// meaning everyone.
+fn chat_listeners(game: &Game, player: &Player) -> Result<(String, Option<Vec<Player>>), String> {
+    let alive = match game.get_statuses().get(player) {
+        Some(status) => status.clone().is_alive(),
+        None => false,
+    };
+    if !alive || game.is_over() {
+        return Err("Permission denied".to_string());
+    }
+    if game.is_silenced(player).unwrap_or(false) {
+        return Err(mafia::Error::Silenced(player.clone()).to_string());
+    }
+
+    match game.phase {
+        Phase::Day(_) => Ok((player.clone(), None)),
+        Phase::Night(_) => {
+            let mut prisoners = game.get_prisoners(player).unwrap_or_default();
+            if !prisoners.is_empty() {
+                prisoners.push(player.clone());
+                Ok(("Jailor".to_string(), Some(prisoners)))
+            } else if let Ok(Some(jailor)) = game.get_jailor(player) {
+                Ok((player.clone(), Some([jailor, player.clone()].to_vec())))
+            } else {
+                Err("Nobody can hear you at night".to_string())
+            }
+        }
+    }
+}
+
 impl Conn {
     fn new(server: Arc<RwLock<ServerState>>, conn: TcpStream, peer: SocketAddr) -> Self {
         let (reader, writer) = conn.into_split();
@@ -285,21 +358,49 @@ impl Conn {
     async fn handle(self: &mut Self, request: Request) -> Result<(), io::Error> {
         let mut state = self.state.write().await;
         match request {
-            Request::Auth(key) => match self.server.read().await.keys.get(&key) {
-                Some(auth) => {
-                    state.auth = auth.clone();
-                    state.send(Response::Authenticated(auth.clone())).await?;
+            Request::Auth(key) => {
+                let server = self.server.read().await;
+                match server.keys.get(&key) {
+                    Some(auth) => {
+                        state.auth = auth.clone();
+                        state.send(Response::Authenticated(auth.clone())).await?;
+
+                        // Catch up on the private events the client couldn't
+                        // see before authenticating, e.g. their role card.
+                        let private: Vec<_> = server
+                            .game
+                            .log
+                            .iter()
+                            .filter(|(visibility, _)| *visibility != Visibility::Public)
+                            .cloned()
+                            .collect();
+                        state.send_events(&server.game, &private).await?;
+                    }
+                    None => {
+                        state
+                            .send(Response::Error("Invalid token".to_string()))
+                            .await?;
+                    }
                 }
-                None => {
+            }
+            Request::EndPhase => match state.auth {
+                Visibility::Moderator => {
+                    std::mem::drop(state);
+                    self.apply_input(&Input::EndPhase).await?;
+                }
+                _ => {
                     state
-                        .send(Response::Error("Invalid token".to_string()))
+                        .send(Response::Error("Permission denied".to_string()))
                         .await?;
                 }
             },
-            Request::EndPhase => match state.auth {
-                Visibility::Moderator => {
+            Request::Say(text) => match state.auth.clone() {
+                Visibility::Player(player) => {
                     std::mem::drop(state);
-                    self.server.write().await.apply(&Input::EndPhase).await?;
+                    let result = self.server.write().await.say(&player, &text).await;
+                    if let Err(e) = result {
+                        self.state.write().await.send(Response::Error(e)).await?;
+                    }
                 }
                 _ => {
                     state
@@ -320,10 +421,19 @@ impl Conn {
         let auth = self.state.read().await.auth.clone();
         match auth {
             Visibility::Player(player) => {
-                self.server
-                    .write()
-                    .await
-                    .apply(&Input::Use(player.clone(), action))
+                // Reject votes from silenced and disenfranchised players outright.
+                if let Action::NoLynch | Action::Unvote | Action::Vote(_) = action {
+                    let result = self.server.read().await.game.check_vote(&player);
+                    if let Err(e) = result {
+                        self.state
+                            .write()
+                            .await
+                            .send(Response::Error(e.to_string()))
+                            .await?;
+                        return Ok(());
+                    }
+                }
+                self.apply_input(&Input::Use(player.clone(), action))
                     .await?;
             }
             _ => {
@@ -337,6 +447,20 @@ impl Conn {
 
         Ok(())
     }
+
+    /// Apply an input to the game, reporting any game errors to the client.
+    async fn apply_input(self: &mut Self, input: &Input) -> Result<(), io::Error> {
+        let result = self.server.write().await.apply(input).await?;
+        if let Err(e) = result {
+            self.state
+                .write()
+                .await
+                .send(Response::Error(e.to_string()))
+                .await?;
+        }
+
+        Ok(())
+    }
 }
 
 impl ConnState {
@@ -372,6 +496,7 @@ impl ConnState {
         updates: &[(Visibility, Event)],
     ) -> Result<(), io::Error> {
         let mut send_players = false;
+        let mut send_summary = false;
 
         for (visibility, event) in updates {
             let visible = match (visibility, &self.auth) {
@@ -395,37 +520,26 @@ impl ConnState {
             send_players |= match event {
                 Event::PhaseBegan(_) => true,
                 Event::Died(_) => true,
+                Event::DiedOfPoison(_) => true,
                 _ => false,
             };
+            send_summary |= *event == Event::GameOver;
         }
 
         if send_players {
             self.send(Response::Players(game.get_statuses())).await?;
         }
 
+        if send_summary {
+            if let Ok(roles) = game.get_roles() {
+                self.send(Response::Summary(roles)).await?;
+            }
+        }
+
         Ok(())
     }
 }
 
-/// Load a serialized value from a file.
-fn load_file<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T, io::Error> {
-    let file = File::open(path).map_err(|e| {
-        io::Error::new(
-            e.kind(),
-            format!("Error opening {}: {}", &path.display(), e),
-        )
-    })?;
-
-    let result = ron::de::from_reader(file).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            format!("Error reading {}: {}", &path.display(), e),
-        )
-    })?;
-
-    Ok(result)
-}
-
 /// Atomically serialize a value to a file.
 ///
 /// Atomicity is achieved by writing to a temporary file then renaming. Renames
@@ -439,3 +553,95 @@ fn save_file<T: serde::ser::Serialize>(path: &PathBuf, value: &T) {
 
     std::fs::rename(tmp_path, path).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use mafia::{Action, Error, Game, Input, State};
+
+    use super::chat_listeners;
+
+    const SETUP: &str = r#"(
+        factions: {
+            "Mafia": [
+                Alignment(Evil),
+                Membership(Visible),
+                Objective(AchieveMajority),
+            ],
+            "Town": [
+                Alignment(Good),
+                Membership(Hidden),
+                Objective(Eliminate(Evil)),
+            ],
+        },
+        players: {
+            "Alice": [
+                Member("Town"),
+            ],
+            "Eve": [
+                Member("Mafia"),
+                Has(Silence("$PLAYER")),
+            ],
+            "Jim": [
+                Member("Town"),
+                Has(Day(Jail("$OTHER_PLAYER"))),
+            ],
+            "Malory": [
+                Member("Mafia"),
+            ],
+        },
+        seed: 0,
+    )"#;
+
+    fn new_game(inputs: &[Input]) -> Game {
+        let state: State = ron::de::from_str(SETUP).unwrap();
+        let mut game = Game::new_from_state(state);
+        for input in inputs {
+            game.try_apply(input).unwrap();
+        }
+        game
+    }
+
+    fn listeners(game: &Game, player: &str) -> Result<(String, Option<Vec<String>>), String> {
+        chat_listeners(game, &player.to_string())
+    }
+
+    #[test]
+    fn test_chat_day() {
+        let game = new_game(&[Input::EndPhase]);
+        assert_eq!(listeners(&game, "Alice"), Ok(("Alice".to_string(), None)));
+    }
+
+    #[test]
+    fn test_chat_jail() {
+        let jail = Input::Use("Jim".to_string(), Action::Jail("Malory".to_string()));
+        let game = new_game(&[Input::EndPhase, jail, Input::EndPhase]);
+        assert_eq!(
+            listeners(&game, "Jim"),
+            Ok((
+                "Jailor".to_string(),
+                Some(vec!["Malory".to_string(), "Jim".to_string()])
+            ))
+        );
+        assert_eq!(
+            listeners(&game, "Malory"),
+            Ok((
+                "Malory".to_string(),
+                Some(vec!["Jim".to_string(), "Malory".to_string()])
+            ))
+        );
+        assert_eq!(
+            listeners(&game, "Alice"),
+            Err("Nobody can hear you at night".to_string())
+        );
+    }
+
+    #[test]
+    fn test_chat_silenced() {
+        let silence = Input::Use("Eve".to_string(), Action::Silence("Alice".to_string()));
+        let game = new_game(&[silence, Input::EndPhase]);
+        assert_eq!(
+            listeners(&game, "Alice"),
+            Err(Error::Silenced("Alice".to_string()).to_string())
+        );
+    }
+}
@@ -1,3 +1,42 @@
-use mafia::{Map, Visibility};
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+
+use mafia::{Map, State, Visibility};
 
 pub type KeyMap = Map<String, Visibility>;
+
+/// Load a serialized value from a file.
+pub fn load_file<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T, io::Error> {
+    let file = File::open(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Error opening {}: {}", &path.display(), e),
+        )
+    })?;
+
+    let result = ron::de::from_reader(file).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("Error reading {}: {}", &path.display(), e),
+        )
+    })?;
+
+    Ok(result)
+}
+
+/// Load a game setup from a file, checking that it is valid.
+pub fn load_setup(path: &PathBuf) -> Result<State, io::Error> {
+    let setup: State = load_file(path)?;
+    setup.validate().map_err(|errors| {
+        for e in &errors {
+            error!("{}: {}", path.display(), e);
+        }
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not a valid setup", path.display()),
+        )
+    })?;
+
+    Ok(setup)
+}
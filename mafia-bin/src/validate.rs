@@ -0,0 +1,11 @@
+use std::io;
+
+use crate::util::load_setup;
+
+pub fn validate(path: std::path::PathBuf) -> Result<(), io::Error> {
+    let setup_path = path.join("setup.ron");
+    load_setup(&setup_path)?;
+    info!("{} is valid.", setup_path.display());
+
+    Ok(())
+}
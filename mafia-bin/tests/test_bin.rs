@@ -16,3 +16,32 @@ async fn test_init() {
         .unwrap()
         .unwrap();
 }
+
+#[tokio::test]
+async fn test_validate() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().to_str().unwrap();
+    util::mafia(&["init", "--path", path])
+        .await
+        .unwrap()
+        .unwrap();
+    util::mafia(&["validate", "--path", path])
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_validate_invalid() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().to_str().unwrap();
+    std::fs::write(
+        tempdir.path().join("setup.ron"),
+        r#"(factions: {}, players: {"Alice": [Member("Town")]}, seed: 0)"#,
+    )
+    .unwrap();
+    util::mafia(&["validate", "--path", path])
+        .await
+        .unwrap()
+        .unwrap_err();
+}
@@ -6,26 +6,106 @@ use crate::util::*;
 /// An action a player can take or has taken in the game.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 pub enum Action {
+    /// Go on alert tonight, becoming bulletproof and killing everyone who
+    /// visits.
+    Alert,
+
+    /// Block a player from using actions.
+    Block(Player),
+
+    /// Cure a player of poison.
+    Cure(Player),
+
     /// An action which is used during the day instead of the night.
     Day(std::boxed::Box<Action>),
 
+    /// Stop a player from voting tomorrow.
+    Disenfranchise(Player),
+
+    /// Make a player appear good to investigations tonight.
+    Disguise(Player),
+
+    /// Douse a player in gasoline.
+    Douse(Player),
+
+    /// Execute a player you jailed.
+    Execute(Player),
+
+    /// Make a player appear evil to investigations tonight.
+    Frame(Player),
+
+    /// Guard a player, dying in their place and killing their attacker if
+    /// they are attacked.
+    Guard(Player),
+
+    /// Kill every doused player.
+    Ignite,
+
     /// Immediately resolve an action.
     Immediate(std::boxed::Box<Action>),
 
     /// Investigate a player's alignment.
     Investigate(Player),
 
+    /// Investigate a player's faction.
+    InvestigateFaction(Player),
+
+    /// Investigate a player's role.
+    InvestigateRole(Player),
+
+    /// Jail a player for the following night, blocking and protecting them.
+    Jail(Player),
+
     /// Kill a player.
     Kill(Player),
 
+    /// Link two players, so that when one dies the other dies too. Only usable
+    /// on the first night.
+    Link(Player, Player),
+
+    /// Vote to eliminate nobody. Allowed by any `Vote` ability.
+    NoLynch,
+
     /// Order a minion to perform an action.
     Order(Player, std::boxed::Box<Action>),
 
+    /// Poison a player, killing them at the end of the following night unless
+    /// they are cured first.
+    ///
+    /// Bulletproof players can't be poisoned, but once poisoned, Bulletproof
+    /// doesn't protect a player from dying of poison. Protected players,
+    /// including jailed ones, can't be poisoned either.
+    Poison(Player),
+
     /// Protect a player from kills.
     Protect(Player),
 
+    /// Recruit a player into your faction.
+    Recruit(Player),
+
+    /// Make the first player's actions target the second player instead.
+    Redirect(Player, Player),
+
+    /// Publicly reveal your role, doubling your vote weight.
+    Reveal,
+
+    /// Stop a player from talking or voting tomorrow.
+    Silence(Player),
+
+    /// Make actions targeting either player target the other instead.
+    Swap(Player, Player),
+
+    /// Learn who a player visited.
+    Track(Player),
+
+    /// Withdraw a vote. Allowed by any `Vote` ability.
+    Unvote,
+
     /// Vote to eliminate a player from the game.
     Vote(Player),
+
+    /// Learn who visited a player.
+    Watch(Player),
 }
 
 impl Action {
@@ -44,15 +124,58 @@ impl Action {
             },
 
             Phase::Night(_) => match (self, action) {
+                (Self::Alert, Self::Alert) => true,
+                (Self::Block(pp), Self::Block(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Cure(pp), Self::Cure(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Disenfranchise(pp), Self::Disenfranchise(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Disguise(pp), Self::Disguise(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Douse(pp), Self::Douse(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Execute(pp), Self::Execute(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Frame(pp), Self::Frame(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Guard(pp), Self::Guard(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Ignite, Self::Ignite) => true,
                 (Self::Immediate(a1), Self::Immediate(a2)) => {
                     a1.matches(phase, actor, faction_members, a2)
                 }
                 (Self::Investigate(pp), Self::Investigate(p)) => {
                     placeholder_matches(pp, actor, faction_members, p)
                 }
+                (Self::InvestigateFaction(pp), Self::InvestigateFaction(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::InvestigateRole(pp), Self::InvestigateRole(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Jail(pp), Self::Jail(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Link(pp1, pp2), Self::Link(p1, p2)) => {
+                    placeholder_matches(pp1, actor, faction_members, p1)
+                        && placeholder_matches(pp2, actor, faction_members, p2)
+                }
+                (Self::Poison(pp), Self::Poison(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
                 (Self::Protect(pp), Self::Protect(p)) => {
                     placeholder_matches(pp, actor, faction_members, p)
                 }
+                (Self::Reveal, Self::Reveal) => true,
                 (Self::Kill(pp), Self::Kill(p)) => {
                     placeholder_matches(pp, actor, faction_members, p)
                 }
@@ -60,24 +183,168 @@ impl Action {
                     placeholder_matches(pp, actor, faction_members, p)
                         && pa.matches(phase, p, faction_members, a)
                 }
+                (Self::Recruit(pp), Self::Recruit(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Redirect(pp1, pp2), Self::Redirect(p1, p2)) => {
+                    placeholder_matches(pp1, actor, faction_members, p1)
+                        && placeholder_matches(pp2, actor, faction_members, p2)
+                }
+                (Self::Silence(pp), Self::Silence(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Swap(pp1, pp2), Self::Swap(p1, p2)) => {
+                    placeholder_matches(pp1, actor, faction_members, p1)
+                        && placeholder_matches(pp2, actor, faction_members, p2)
+                }
+                (Self::Track(pp), Self::Track(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
+                (Self::Vote(_), Self::NoLynch) => true,
+                (Self::Vote(_), Self::Unvote) => true,
                 (Self::Vote(pp), Self::Vote(p)) => {
                     placeholder_matches(pp, actor, faction_members, p)
                 }
+                (Self::Watch(pp), Self::Watch(p)) => {
+                    placeholder_matches(pp, actor, faction_members, p)
+                }
                 _ => false,
             },
         }
     }
 
+    /// Return the player who carries out this action when used by the given
+    /// player, and the action they carry out.
+    pub fn performer<'a>(self: &'a Self, player: &'a Player) -> (&'a Player, &'a Action) {
+        match self {
+            Self::Order(minion, a) => a.performer(minion),
+            _ => (player, self),
+        }
+    }
+
     /// Return resolution priority. Lower numbers are resolved first.
     pub fn precedence(self: &Self) -> usize {
         match self {
+            Self::Alert => 0,
+            Self::Block(_) => 10,
+            Self::Cure(_) => 1010,
             Self::Day(a) => a.precedence(),
+            Self::Disenfranchise(_) => 1000,
+            Self::Disguise(_) => 15,
+            Self::Douse(_) => 40,
+            Self::Execute(_) => 1000,
+            Self::Frame(_) => 15,
+            Self::Guard(_) => 30,
+            Self::Ignite => 1000,
             Self::Immediate(_) => 0,
-            Self::Investigate(_) => 1,
-            Self::Protect(_) => 2,
+            Self::Investigate(_) => 20,
+            Self::InvestigateFaction(_) => 20,
+            Self::InvestigateRole(_) => 20,
+            Self::Jail(_) => 10,
+            Self::Protect(_) => 30,
+            Self::Recruit(_) => 1000,
+            Self::Redirect(_, _) => 12,
+            Self::Reveal => 0,
             Self::Kill(_) => 1000,
+            Self::Link(_, _) => 0,
+            Self::NoLynch => 1000,
             Self::Order(_, a) => a.precedence(),
+            Self::Poison(_) => 1000,
+            Self::Silence(_) => 1000,
+            Self::Swap(_, _) => 12,
+            Self::Track(_) => 2000,
+            Self::Unvote => 1000,
             Self::Vote(_) => 1000,
+            Self::Watch(_) => 2000,
+        }
+    }
+
+    /// Return this action with each target replaced, leaving the minion of an
+    /// order alone.
+    pub fn retarget<F: Fn(&Player) -> Player>(self: &Self, f: &F) -> Action {
+        match self {
+            Self::Alert => Self::Alert,
+            Self::Block(p) => Self::Block(f(p)),
+            Self::Cure(p) => Self::Cure(f(p)),
+            Self::Day(a) => Self::Day(Box::new(a.retarget(f))),
+            Self::Disenfranchise(p) => Self::Disenfranchise(f(p)),
+            Self::Disguise(p) => Self::Disguise(f(p)),
+            Self::Douse(p) => Self::Douse(f(p)),
+            Self::Execute(p) => Self::Execute(f(p)),
+            Self::Frame(p) => Self::Frame(f(p)),
+            Self::Guard(p) => Self::Guard(f(p)),
+            Self::Ignite => Self::Ignite,
+            Self::Immediate(a) => Self::Immediate(Box::new(a.retarget(f))),
+            Self::Investigate(p) => Self::Investigate(f(p)),
+            Self::InvestigateFaction(p) => Self::InvestigateFaction(f(p)),
+            Self::InvestigateRole(p) => Self::InvestigateRole(f(p)),
+            Self::Jail(p) => Self::Jail(f(p)),
+            Self::Kill(p) => Self::Kill(f(p)),
+            Self::Link(p1, p2) => Self::Link(f(p1), f(p2)),
+            Self::NoLynch => Self::NoLynch,
+            Self::Order(m, a) => Self::Order(m.clone(), Box::new(a.retarget(f))),
+            Self::Poison(p) => Self::Poison(f(p)),
+            Self::Protect(p) => Self::Protect(f(p)),
+            Self::Recruit(p) => Self::Recruit(f(p)),
+            Self::Redirect(p1, p2) => Self::Redirect(f(p1), f(p2)),
+            Self::Reveal => Self::Reveal,
+            Self::Silence(p) => Self::Silence(f(p)),
+            Self::Swap(p1, p2) => Self::Swap(f(p1), f(p2)),
+            Self::Track(p) => Self::Track(f(p)),
+            Self::Unvote => Self::Unvote,
+            Self::Vote(p) => Self::Vote(f(p)),
+            Self::Watch(p) => Self::Watch(f(p)),
+        }
+    }
+
+    /// Return the players this action refers to.
+    pub fn targets(self: &Self) -> Vec<Player> {
+        match self {
+            Self::Alert => Vec::new(),
+            Self::Block(p) => [p.clone()].to_vec(),
+            Self::Cure(p) => [p.clone()].to_vec(),
+            Self::Day(a) => a.targets(),
+            Self::Disenfranchise(p) => [p.clone()].to_vec(),
+            Self::Disguise(p) => [p.clone()].to_vec(),
+            Self::Douse(p) => [p.clone()].to_vec(),
+            Self::Execute(p) => [p.clone()].to_vec(),
+            Self::Frame(p) => [p.clone()].to_vec(),
+            Self::Guard(p) => [p.clone()].to_vec(),
+            Self::Ignite => Vec::new(),
+            Self::Immediate(a) => a.targets(),
+            Self::Investigate(p) => [p.clone()].to_vec(),
+            Self::InvestigateFaction(p) => [p.clone()].to_vec(),
+            Self::InvestigateRole(p) => [p.clone()].to_vec(),
+            Self::Jail(p) => [p.clone()].to_vec(),
+            Self::Kill(p) => [p.clone()].to_vec(),
+            Self::Link(p1, p2) => [p1.clone(), p2.clone()].to_vec(),
+            Self::NoLynch => Vec::new(),
+            Self::Order(p, a) => [[p.clone()].to_vec(), a.targets()].concat(),
+            Self::Poison(p) => [p.clone()].to_vec(),
+            Self::Protect(p) => [p.clone()].to_vec(),
+            Self::Recruit(p) => [p.clone()].to_vec(),
+            Self::Redirect(p1, p2) => [p1.clone(), p2.clone()].to_vec(),
+            Self::Reveal => Vec::new(),
+            Self::Silence(p) => [p.clone()].to_vec(),
+            Self::Swap(p1, p2) => [p1.clone(), p2.clone()].to_vec(),
+            Self::Track(p) => [p.clone()].to_vec(),
+            Self::Unvote => Vec::new(),
+            Self::Vote(p) => [p.clone()].to_vec(),
+            Self::Watch(p) => [p.clone()].to_vec(),
+        }
+    }
+
+    /// Return the players visited by the performer of this action.
+    pub fn visits(self: &Self) -> Vec<Player> {
+        match self {
+            Self::Day(_) => Vec::new(),
+            Self::Execute(_) => Vec::new(),
+            Self::Immediate(_) => Vec::new(),
+            Self::NoLynch => Vec::new(),
+            Self::Order(_, a) => a.visits(),
+            Self::Unvote => Vec::new(),
+            Self::Vote(_) => Vec::new(),
+            _ => self.targets(),
         }
     }
 }
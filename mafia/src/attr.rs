@@ -5,6 +5,7 @@ use crate::alignment::*;
 use crate::membership::*;
 use crate::objective::*;
 use crate::phase::*;
+use crate::sanity::*;
 use crate::util::*;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
@@ -18,9 +19,32 @@ pub enum Attr {
     /// Player only: Player is dead.
     Dead,
 
+    /// Player only: Player shows up as the given alignment to investigations.
+    DetectsAs(Alignment),
+
+    /// Player only: Player can't vote.
+    Disenfranchised,
+
+    /// Player only: Player is doused in gasoline, and dies when ignited.
+    Doused,
+
+    /// Player only: Player is guarded by the given bodyguard, who dies in their
+    /// place if they are attacked.
+    Guarded(Player),
+
     /// Faction or player: Faction leader or player has the given action.
     Has(Action),
 
+    /// Player only: Player is jailed by the given jailor, who blocks and
+    /// protects them and may execute them.
+    Jailed(Player),
+
+    /// Player only: Player dies of heartbreak when the given player dies.
+    LinkedTo(Player),
+
+    /// Player only: Player was eliminated by vote.
+    Lynched,
+
     /// Player only: Player belongs to the given faction.
     Member(
         /// Faction player belongs to.
@@ -36,6 +60,10 @@ pub enum Attr {
     /// Faction only: Win condition.
     Objective(Objective),
 
+    /// Player only: Player performs the given action whenever another player
+    /// visits them at night. `$VISITOR` stands for the visiting player.
+    OnVisited(Box<Action>),
+
     /// An attribute that expires after a given number of phases.
     Phases(
         /// Number of phases this attribute lasts.
@@ -47,11 +75,41 @@ pub enum Attr {
     /// Player only: Player will die after a number of phases.
     Poisoned(u64),
 
-    /// Player only: Player received a number of elimination votes.
+    /// Player only: Player is protected from kills by the given player.
+    Protected(Player),
+
+    /// Player only: Player receives a number of extra elimination votes.
     ReceivedVotes(i64),
 
+    /// Player only: Player has publicly revealed their role, doubling their
+    /// vote weight.
+    Revealed,
+
+    /// Player only: How the player's investigations are skewed.
+    Sanity(Sanity),
+
+    /// Player only: Player can't talk or vote.
+    Silenced,
+
     /// An attribute which can't be used for another phase
     Tapped(Box<Attr>),
+
+    /// Player only: Player can't be recruited into another faction.
+    Unconvertible,
+
+    /// An attribute that expires after it is used a given number of times.
+    Uses(
+        /// Number of uses remaining.
+        u64,
+        /// The limited-use attribute.
+        Box<Attr>,
+    ),
+
+    /// Player only: Player visited the given player.
+    Visited(Player),
+
+    /// Player only: Player's vote counts this many times.
+    VoteWeight(i64),
 }
 
 impl Attr {
@@ -66,14 +124,29 @@ impl Attr {
         match self {
             Self::Has(a) => a.matches(phase, actor, faction_members, action),
             Self::Phases(_, a) => a.allows_action(phase, actor, faction_members, action),
+            Self::Uses(0, _) => false,
+            Self::Uses(_, a) => a.allows_action(phase, actor, faction_members, action),
             _ => false,
         }
     }
 
+    /// Return the action this attribute grants, even if currently unusable.
+    pub fn get_ability(self: &Self) -> Option<Action> {
+        match self {
+            Self::Has(a) => Some(a.clone()),
+            Self::Phases(_, a) => a.get_ability(),
+            Self::Tapped(a) => a.get_ability(),
+            Self::Uses(_, a) => a.get_ability(),
+            _ => None,
+        }
+    }
+
     pub fn get_action(self: &Self) -> Option<Action> {
         match self {
             Self::Has(a) => Some(a.clone()),
             Self::Phases(_, a) => a.get_action(),
+            Self::Uses(0, _) => None,
+            Self::Uses(_, a) => a.get_action(),
             _ => None,
         }
     }
@@ -86,6 +159,14 @@ impl Attr {
         }
     }
 
+    pub fn get_detects_as(self: &Self) -> Option<Alignment> {
+        match self {
+            Self::DetectsAs(a) => Some(a.clone()),
+            Self::Phases(_, a) => a.get_detects_as(),
+            _ => None,
+        }
+    }
+
     pub fn get_faction_and_rank(self: &Self) -> Option<(Faction, i64)> {
         match self {
             Self::Member(f, r) => Some((f.clone(), *r)),
@@ -94,6 +175,38 @@ impl Attr {
         }
     }
 
+    pub fn get_guard(self: &Self) -> Option<Player> {
+        match self {
+            Self::Guarded(p) => Some(p.clone()),
+            Self::Phases(_, a) => a.get_guard(),
+            _ => None,
+        }
+    }
+
+    pub fn get_jailor(self: &Self) -> Option<Player> {
+        match self {
+            Self::Jailed(p) => Some(p.clone()),
+            Self::Phases(_, a) => a.get_jailor(),
+            _ => None,
+        }
+    }
+
+    pub fn get_linked(self: &Self) -> Option<Player> {
+        match self {
+            Self::LinkedTo(p) => Some(p.clone()),
+            Self::Phases(_, a) => a.get_linked(),
+            _ => None,
+        }
+    }
+
+    pub fn get_membership(self: &Self) -> Option<Membership> {
+        match self {
+            Self::Membership(m) => Some(m.clone()),
+            Self::Phases(_, m) => m.get_membership(),
+            _ => None,
+        }
+    }
+
     pub fn get_objective(self: &Self) -> Option<Objective> {
         match self {
             Self::Objective(o) => Some(o.clone()),
@@ -102,6 +215,54 @@ impl Attr {
         }
     }
 
+    pub fn get_on_visited(self: &Self) -> Option<Action> {
+        match self {
+            Self::OnVisited(a) => Some((**a).clone()),
+            Self::Phases(_, a) => a.get_on_visited(),
+            _ => None,
+        }
+    }
+
+    pub fn get_poisoned(self: &Self) -> Option<u64> {
+        match self {
+            Self::Poisoned(n) => Some(*n),
+            Self::Phases(_, a) => a.get_poisoned(),
+            _ => None,
+        }
+    }
+
+    pub fn get_protector(self: &Self) -> Option<Player> {
+        match self {
+            Self::Protected(p) => Some(p.clone()),
+            Self::Phases(_, a) => a.get_protector(),
+            _ => None,
+        }
+    }
+
+    pub fn get_sanity(self: &Self) -> Option<Sanity> {
+        match self {
+            Self::Sanity(s) => Some(s.clone()),
+            Self::Phases(_, a) => a.get_sanity(),
+            _ => None,
+        }
+    }
+
+    pub fn get_vote_weight(self: &Self) -> Option<i64> {
+        match self {
+            Self::VoteWeight(n) => Some(*n),
+            Self::Phases(_, a) => a.get_vote_weight(),
+            _ => None,
+        }
+    }
+
+    pub fn get_visited(self: &Self) -> Option<Player> {
+        match self {
+            Self::Visited(p) => Some(p.clone()),
+            Self::Phases(_, a) => a.get_visited(),
+            _ => None,
+        }
+    }
+
     pub fn is_alive(self: &Self) -> Option<bool> {
         match self {
             Self::Dead => Some(false),
@@ -118,11 +279,62 @@ impl Attr {
         }
     }
 
+    pub fn is_disenfranchised(self: &Self) -> Option<bool> {
+        match self {
+            Self::Disenfranchised => Some(true),
+            Self::Phases(_, a) => a.is_disenfranchised(),
+            _ => None,
+        }
+    }
+
+    pub fn is_doused(self: &Self) -> Option<bool> {
+        match self {
+            Self::Doused => Some(true),
+            Self::Phases(_, a) => a.is_doused(),
+            _ => None,
+        }
+    }
+
+    pub fn is_lynched(self: &Self) -> Option<bool> {
+        match self {
+            Self::Lynched => Some(true),
+            Self::Phases(_, a) => a.is_lynched(),
+            _ => None,
+        }
+    }
+
+    pub fn is_revealed(self: &Self) -> Option<bool> {
+        match self {
+            Self::Revealed => Some(true),
+            Self::Phases(_, a) => a.is_revealed(),
+            _ => None,
+        }
+    }
+
+    pub fn is_silenced(self: &Self) -> Option<bool> {
+        match self {
+            Self::Silenced => Some(true),
+            Self::Phases(_, a) => a.is_silenced(),
+            _ => None,
+        }
+    }
+
+    pub fn is_unconvertible(self: &Self) -> Option<bool> {
+        match self {
+            Self::Unconvertible => Some(true),
+            Self::Phases(_, a) => a.is_unconvertible(),
+            _ => None,
+        }
+    }
+
     pub fn next_phase(self: &Self) -> Option<Self> {
         match self {
             Self::Phases(1, _) => None,
-            Self::Phases(n, a) => Some(Self::Phases(n - 1, a.clone())),
-            Self::Tapped(a) => Some((**a).clone()),
+            Self::Phases(n, a) => Some(Self::Phases(n - 1, Box::new(a.next_phase()?))),
+            Self::Poisoned(n) => Some(Self::Poisoned(n.saturating_sub(1))),
+            Self::Tapped(a) => a.next_phase(),
+            Self::Uses(0, _) => None,
+            Self::Uses(n, a) => Some(Self::Uses(*n, Box::new(a.next_phase()?))),
             _ => Some(self.clone()),
         }
     }
@@ -137,6 +349,15 @@ impl Attr {
 
     /// Return used version of the attribute.
     pub fn tap(self: &Self) -> Self {
-        Attr::Tapped(Box::new(self.clone()))
+        Attr::Tapped(Box::new(self.use_once()))
+    }
+
+    /// Return the attribute with one fewer use remaining, if limited.
+    fn use_once(self: &Self) -> Self {
+        match self {
+            Self::Phases(n, a) => Self::Phases(*n, Box::new(a.use_once())),
+            Self::Uses(n, a) => Self::Uses(n.saturating_sub(1), a.clone()),
+            _ => self.clone(),
+        }
     }
 }
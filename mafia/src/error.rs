@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::*;
+
+/// Error caused by invalid input or an invalid game setup.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Error {
+    /// Dead players can't use actions.
+    DeadPlayer(Player),
+
+    /// Player can't vote today.
+    Disenfranchised(Player),
+
+    /// Game has already ended.
+    GameOver,
+
+    /// Faction does not have an alignment.
+    MissingAlignment(Faction),
+
+    /// Player does not belong to a faction.
+    MissingFaction(Player),
+
+    /// Faction does not have an objective.
+    MissingObjective(Faction),
+
+    /// Player belongs to more than one faction.
+    MultipleFactions(Player),
+
+    /// Player doesn't have the action or can't use it right now.
+    NotAllowed,
+
+    /// Player has no vote to withdraw.
+    NotVoting,
+
+    /// Player can't talk or vote today.
+    Silenced(Player),
+
+    /// No such faction.
+    UnknownFaction(Faction),
+
+    /// Player or faction uses a placeholder that isn't valid there.
+    UnknownPlaceholder(PlayerOrFaction, String),
+
+    /// No such player.
+    UnknownPlayer(Player),
+
+    /// Player or faction refers to a player that doesn't exist.
+    UnknownTarget(PlayerOrFaction, Player),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::DeadPlayer(player) => write!(f, "Player is dead: {:?}", player),
+            Self::Disenfranchised(player) => write!(f, "Player can't vote today: {:?}", player),
+            Self::GameOver => write!(f, "Game is over"),
+            Self::MissingAlignment(faction) => {
+                write!(f, "Faction does not have an alignment: {:?}", faction)
+            }
+            Self::MissingFaction(player) => {
+                write!(f, "Player does not have a faction: {:?}", player)
+            }
+            Self::MissingObjective(faction) => {
+                write!(f, "Faction does not have an objective: {:?}", faction)
+            }
+            Self::MultipleFactions(player) => {
+                write!(f, "Player belongs to multiple factions: {:?}", player)
+            }
+            Self::NotAllowed => write!(f, "Action not allowed"),
+            Self::NotVoting => write!(f, "Not voting"),
+            Self::Silenced(player) => {
+                write!(f, "Player can't talk or vote today: {:?}", player)
+            }
+            Self::UnknownFaction(faction) => write!(f, "No such faction: {:?}", faction),
+            Self::UnknownPlaceholder(owner, placeholder) => {
+                write!(f, "Unknown placeholder in {:?}: {:?}", owner, placeholder)
+            }
+            Self::UnknownPlayer(player) => write!(f, "No such player: {:?}", player),
+            Self::UnknownTarget(owner, player) => {
+                write!(f, "No such player in {:?}: {:?}", owner, player)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -2,9 +2,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::action::*;
 use crate::alignment::*;
+use crate::error::*;
 use crate::input::*;
 use crate::phase::*;
+use crate::role::*;
 use crate::util::*;
+use crate::vote::*;
 
 /// Event that occurred during a game.
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
@@ -12,30 +15,112 @@ pub enum Event {
     /// Action was accepted and resolved.
     Accepted(Player, Action),
 
+    /// Action was blocked and had no effect.
+    Blocked(Player, Action),
+
+    /// Player was recruited into the given faction.
+    Converted(Player, Faction),
+
     /// Player died.
     Died(Player),
 
+    /// Player died of heartbreak after a linked player died.
+    DiedOfHeartbreak(Player),
+
+    /// Player died of poison.
+    DiedOfPoison(Player),
+
+    /// Player can't vote today.
+    Disenfranchised(Player),
+
+    /// Game ended with no winners.
+    Draw,
+
+    /// Faction's living members, in order of rank.
+    FactionMembers(Faction, Vec<Player>),
+
     /// Investigation yielded alignment.
     FoundAlignment(Player, Alignment),
 
+    /// Investigation yielded faction.
+    FoundFaction(Player, Faction),
+
+    /// Investigation yielded role.
+    FoundRole(Player, RoleCard),
+
+    /// Tracking yielded the players a player visited.
+    FoundVisits(Player, Vec<Player>),
+
+    /// Watching yielded the players who visited a player.
+    FoundVisitors(Player, Vec<Player>),
+
+    /// Game ended. No further input is accepted.
+    GameOver,
+
     /// Game received input.
     Input(Input),
 
+    /// Action was submitted but can't be used.
+    Invalid(Player, Action, Error),
+
+    /// Player was jailed for the night.
+    Jailed(Player),
+
+    /// Players were linked, sharing their fate.
+    Linked(Player, Player),
+
+    /// Player was poisoned.
+    Poisoned(Player),
+
     /// Phase started.
     PhaseBegan(Phase),
 
     /// Phase ended.
     PhaseEnded(Phase),
 
+    /// Action was given new targets by a redirect or swap.
+    Retargeted(Player, Action, Action),
+
     /// Action was rejected because it was invalid or amended.
     Rejected(Player, Action),
 
+    /// Player's role was revealed.
+    Revealed(Player, RoleCard),
+
+    /// Player was assigned a role at the start of the game.
+    RoleAssigned(Player, RoleCard),
+
+    /// Player was saved from an attack by the player seeing this event.
+    Saved(Player),
+
+    /// Player can't talk or vote today.
+    Silenced(Player),
+
+    /// Action was submitted and will be resolved at the end of the phase
+    /// unless amended.
+    Submitted(Player, Action),
+
+    /// Player was visited, triggering an action.
+    Triggered(Player, Action),
+
     /// Player used action.
     Used(Player, Action),
 
+    /// Player withdrew their vote.
+    Unvoted(Player),
+
+    /// Current votes for each ballot, in the order they were cast.
+    VoteCount(Map<Ballot, Vec<Player>>),
+
+    /// Final vote totals for each ballot.
+    VoteTotals(Map<Ballot, i64>),
+
     /// Player voted to eliminate another player.
     VotedFor(Player, Player),
 
+    /// Player voted to eliminate nobody.
+    VotedNoLynch(Player),
+
     /// Faction won.
     Won(Faction),
 }
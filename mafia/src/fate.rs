@@ -11,6 +11,6 @@ pub enum Fate {
     // Would win if the game were to end right now.
     Winning,
 
-    // Has won the game, ending it.
+    // Has won the game, ending it. Every other faction that's winning wins too.
     Won,
 }
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// What is publicly revealed about a player when they die.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Flip {
+    /// Nothing is revealed.
+    #[default]
+    Hidden,
+
+    /// Alignment is revealed.
+    Alignment,
+
+    /// Alignment and faction are revealed.
+    Faction,
+
+    /// Alignment, faction, and abilities are revealed.
+    Role,
+}
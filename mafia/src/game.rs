@@ -7,27 +7,52 @@ use serde::{Deserialize, Serialize};
 use crate::action::*;
 use crate::alignment::*;
 use crate::attr::*;
+use crate::error::*;
 use crate::event::*;
 use crate::fate::*;
+use crate::flip::*;
 use crate::input::*;
 use crate::log::*;
+use crate::membership::*;
 use crate::objective::*;
 use crate::phase::*;
 use crate::player::*;
+use crate::role::*;
+use crate::sanity::*;
 use crate::state::*;
 use crate::util::*;
 use crate::visibility::*;
+use crate::vote::*;
 
 type Rng = rand_xoshiro::Xoshiro256StarStar;
 
 type Plan = Vec<(Player, Action)>;
 
+/// Number of phases a poisoned player has left to live.
+const POISON_PHASES: u64 = 3;
+
+/// Placeholder for the visiting player in an action triggered by a visit.
+const VISITOR: &str = "$VISITOR";
+
+/// Location of the attribute allowing an action to be used.
+enum ActionSource {
+    /// Index into a faction's attributes.
+    Faction(Faction, usize),
+
+    /// Index into the acting player's attributes.
+    Player(usize),
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Game {
     pub start: State,
     pub state: State,
     pub phase: Phase,
     pub log: Log,
+
+    /// Whether the game has ended.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub over: bool,
 }
 
 impl Game {
@@ -35,60 +60,340 @@ impl Game {
         Self::new_from_state(State::new())
     }
 
+    /// Start a game from a setup, panicking if it is invalid.
+    ///
+    /// See `try_new_from_state` for a non-panicking version.
     pub fn new_from_state(state: State) -> Self {
+        match Self::try_new_from_state(state) {
+            Ok(game) => game,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Start a game from a setup, telling each player their role.
+    pub fn try_new_from_state(state: State) -> Result<Self> {
         let phase = Phase::Night(0);
-        Game {
+        let mut game = Game {
             start: state.clone(),
             state: state,
             phase: phase.clone(),
             log: [(Visibility::Public, Event::PhaseBegan(phase))].to_vec(),
+            over: false,
+        };
+
+        // Tell players their roles.
+        for player in game.state.players.keys() {
+            let card = game.get_role_card(player, &Flip::Role)?;
+            game.log.push((
+                Visibility::Player(player.clone()),
+                Event::RoleAssigned(player.clone(), card),
+            ));
         }
+
+        // Introduce members of visible factions to each other.
+        let factions: Vec<Faction> = game.state.factions.keys().cloned().collect();
+        for faction in &factions {
+            game.log_faction_members(faction)?;
+        }
+
+        Ok(game)
     }
 
+    /// Apply an input to the game, panicking if it is invalid.
+    ///
+    /// See `try_apply` for a non-panicking version.
     pub fn apply(self: &mut Self, input: &Input) -> &[(Visibility, Event)] {
+        match self.try_apply(input) {
+            Ok(log) => log,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Apply an input to the game, returning the events it caused.
+    ///
+    /// If the input is invalid, the game is left unchanged.
+    pub fn try_apply(self: &mut Self, input: &Input) -> Result<&[(Visibility, Event)]> {
         let log_start = self.log.len();
+        let phase = self.phase.clone();
+        let state = self.state.clone();
+        let over = self.over;
+
+        if let Err(e) = self.apply_input(input) {
+            self.log.truncate(log_start);
+            self.phase = phase;
+            self.state = state;
+            self.over = over;
+            return Err(e);
+        }
+
+        Ok(&self.log[log_start..])
+    }
+
+    /// Return an error if a given player can't vote right now.
+    pub fn check_vote(self: &Self, player: &Player) -> Result<()> {
+        if self.is_silenced(player)? {
+            Err(Error::Silenced(player.clone()))
+        } else if self.get_attr_or(player, |a| a.is_disenfranchised(), false)? {
+            Err(Error::Disenfranchised(player.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Return the player jailing a given player, if any.
+    pub fn get_jailor(self: &Self, prisoner: &Player) -> Result<Option<Player>> {
+        self.get_attr(prisoner, |a| a.get_jailor())
+    }
+
+    /// Return the players jailed by a given player.
+    pub fn get_prisoners(self: &Self, jailor: &Player) -> Result<Vec<Player>> {
+        let mut prisoners = Vec::new();
+        for player in self.state.players.keys() {
+            if self.get_jailor(player)?.as_ref() == Some(jailor) {
+                prisoners.push(player.clone());
+            }
+        }
+        Ok(prisoners)
+    }
+
+    /// Return every player's full role card.
+    pub fn get_roles(self: &Self) -> Result<Map<Player, RoleCard>> {
+        let mut roles = Map::new();
+        for player in self.state.players.keys() {
+            roles.insert(player.clone(), self.get_role_card(player, &Flip::Role)?);
+        }
+        Ok(roles)
+    }
+
+    pub fn get_statuses(self: &Self) -> Map<Player, PlayerStatus> {
+        self.state
+            .players
+            .keys()
+            .filter_map(|p| Some((p.clone(), self.get_status(p).ok()?)))
+            .collect()
+    }
+
+    /// Return whether a given player is silenced.
+    pub fn is_silenced(self: &Self, player: &Player) -> Result<bool> {
+        self.get_attr_or(player, |a| a.is_silenced(), false)
+    }
+
+    /// Return whether the game has ended.
+    pub fn is_over(self: &Self) -> bool {
+        self.over
+    }
+
+    fn add_attr(self: &mut Self, player: &Player, attr: Attr) -> Result<()> {
+        self.state
+            .players
+            .get_mut(player)
+            .ok_or_else(|| Error::UnknownPlayer(player.clone()))?
+            .push(attr);
+        Ok(())
+    }
+
+    fn apply_input(self: &mut Self, input: &Input) -> Result<()> {
+        if self.is_over() {
+            return Err(Error::GameOver);
+        }
+        if let Input::Use(player, action) = input {
+            self.check_player(player)?;
+            for target in action.targets() {
+                self.check_player(&target)?;
+            }
+        }
+
         self.log
             .push((Visibility::Moderator, Event::Input(input.clone())));
 
         match input {
-            Input::EndPhase => self.resolve(),
-            Input::Use(player, action) => match action {
-                Action::Immediate(action) => {
-                    self.resolve_action(player, action);
-                }
-                Action::Order(minion, faction_action) => match &**faction_action {
-                    Action::Immediate(a) => {
-                        self.resolve_action(minion, &*a);
+            Input::EndPhase => self.resolve()?,
+            Input::Use(player, action) => {
+                let mut result = self.check_action(player, action).map(|_| ());
+                if result.is_ok() && *action == Action::Unvote && !self.is_voting(player)? {
+                    result = Err(Error::NotVoting);
+                }
+                if let Err(e) = result {
+                    self.log.push((
+                        Visibility::Player(player.clone()),
+                        Event::Invalid(player.clone(), action.clone(), e),
+                    ));
+                    return Ok(());
+                }
+
+                self.log.push((
+                    Visibility::Player(player.clone()),
+                    Event::Submitted(player.clone(), action.clone()),
+                ));
+
+                // Resolve immediate actions now, using up the ability that
+                // allows them so they can't be repeated this phase.
+                if let (performer, Action::Immediate(a)) = action.performer(player) {
+                    if let Some(attr) = self.resolve_get_action(player, action) {
+                        *attr = attr.tap();
+                    }
+                    self.resolve_action(performer, a)?;
+                }
+
+                match action {
+                    Action::NoLynch => {
+                        self.log.push((
+                            self.get_vote_visibility(),
+                            Event::VotedNoLynch(player.clone()),
+                        ));
+                    }
+                    Action::Unvote => {
+                        self.log
+                            .push((self.get_vote_visibility(), Event::Unvoted(player.clone())));
+                    }
+                    Action::Vote(target) => {
+                        self.log.push((
+                            self.get_vote_visibility(),
+                            Event::VotedFor(player.clone(), target.clone()),
+                        ));
                     }
                     _ => {}
-                },
-                Action::Vote(target) => {
+                }
+
+                // Publish the new tally, ending the day as soon as a majority
+                // is reached. Immediate actions may change vote weights.
+                let is_vote = matches!(action, Action::NoLynch | Action::Unvote | Action::Vote(_));
+                let is_immediate = matches!(action.performer(player).1, Action::Immediate(_));
+                if is_vote || (is_immediate && matches!(self.phase, Phase::Day(_))) {
                     self.log.push((
-                        Visibility::Public,
-                        Event::VotedFor(player.clone(), target.clone()),
+                        self.get_vote_visibility(),
+                        Event::VoteCount(self.get_wagons()?),
                     ));
+                    if self.is_hammered()? {
+                        self.resolve()?;
+                    }
                 }
-                _ => {}
-            },
+            }
         }
 
-        &self.log[log_start..]
+        Ok(())
     }
 
-    pub fn get_statuses(self: &Self) -> Map<Player, PlayerStatus> {
-        self.state
-            .players
-            .keys()
-            .map(|p| (p.clone(), self.get_status(p)))
-            .collect()
+    /// Check whether a player can use an action right now.
+    ///
+    /// Returns the first attribute allowing the action, checking faction
+    /// actions before individual ones.
+    fn check_action(self: &Self, player: &Player, action: &Action) -> Result<ActionSource> {
+        if !self.is_alive(player)? {
+            return Err(Error::DeadPlayer(player.clone()));
+        }
+
+        // Players can only be linked on the first night.
+        if let Action::Link(_, _) = action.performer(player).1 {
+            if self.phase != Phase::Night(0) {
+                return Err(Error::NotAllowed);
+            }
+        }
+
+        // Silenced and disenfranchised players can't vote.
+        if let Action::NoLynch | Action::Unvote | Action::Vote(_) = action {
+            self.check_vote(player)?;
+        }
+
+        // Only a jailor's own living prisoners can be executed.
+        if let Action::Execute(target) = action {
+            if self.get_jailor(target)?.as_ref() != Some(player) || !self.is_alive(target)? {
+                return Err(Error::NotAllowed);
+            }
+        }
+
+        // Check faction actions.
+        for (faction, attrs) in &self.state.factions {
+            if self.get_leader(faction)?.as_ref() != Some(player) {
+                continue;
+            }
+            let members = Set::from_iter(self.get_members(faction)?.into_iter());
+            for (i, attr) in attrs.iter().enumerate() {
+                if attr.allows_action(&self.phase, player, &members, action) {
+                    return Ok(ActionSource::Faction(faction.clone(), i));
+                }
+            }
+        }
+
+        // Check individual actions.
+        for (i, attr) in self.get_attrs(player)?.rev().enumerate() {
+            if let Some(a) = attr.get_action() {
+                if a.matches(&self.phase, player, &Set::new(), action) {
+                    return Ok(ActionSource::Player(i));
+                }
+            }
+        }
+
+        Err(Error::NotAllowed)
     }
 
-    fn add_attr(self: &mut Self, player: &Player, attr: Attr) {
-        self.state.players.get_mut(player).unwrap().push(attr);
+    /// Return an error if the given player doesn't exist.
+    fn check_player(self: &Self, player: &Player) -> Result<()> {
+        if self.state.players.contains_key(player) {
+            Ok(())
+        } else {
+            Err(Error::UnknownPlayer(player.clone()))
+        }
     }
 
-    fn get_attr<T, F: FnMut(&Attr) -> Option<T>>(self: &Self, player: &Player, f: F) -> Option<T> {
-        self.get_attrs(player).find_map(f)
+    /// Count the votes cast by living players this phase.
+    ///
+    /// Returns each ballot's vote count, including any extra votes players
+    /// receive, along with the log index at which the ballot first led with
+    /// that many votes.
+    fn count_votes(self: &Self) -> Result<Map<Ballot, (i64, usize)>> {
+        // Extra votes count from the start of the phase.
+        let mut counts: Map<Ballot, i64> = Map::new();
+        for player in self.get_living_players()? {
+            let extra = self.num_votes_for(&player)?;
+            if extra != 0 {
+                counts.insert(Ballot::Player(player), extra);
+            }
+        }
+
+        // Note the first time each ballot leads with a given count.
+        let mut led: Map<(Ballot, i64), usize> = Map::new();
+        let mut note_leaders = |counts: &Map<Ballot, i64>, i: usize| {
+            let max = counts.values().cloned().max().unwrap_or(0);
+            for (ballot, count) in counts {
+                if *count > 0 && *count == max {
+                    led.entry((ballot.clone(), *count)).or_insert(i);
+                }
+            }
+        };
+        note_leaders(&counts, 0);
+
+        let mut ballots = Map::new();
+        for (i, voter, ballot) in self.get_vote_changes()? {
+            if ballots.get(&voter) == ballot.as_ref() {
+                continue;
+            }
+            let weight = self.get_vote_weight(&voter)?;
+            if let Some(old) = ballots.remove(&voter) {
+                *counts.entry(old).or_default() -= weight;
+            }
+            if let Some(ballot) = ballot {
+                ballots.insert(voter, ballot.clone());
+                *counts.entry(ballot).or_default() += weight;
+            }
+            note_leaders(&counts, i);
+        }
+
+        Ok(counts
+            .into_iter()
+            .map(|(ballot, count)| {
+                let i = led.get(&(ballot.clone(), count)).cloned();
+                (ballot, (count, i.unwrap_or(usize::MAX)))
+            })
+            .collect())
+    }
+
+    fn get_attr<T, F: FnMut(&Attr) -> Option<T>>(
+        self: &Self,
+        player: &Player,
+        f: F,
+    ) -> Result<Option<T>> {
+        Ok(self.get_attrs(player)?.find_map(f))
     }
 
     fn get_attr_or<T, F: FnMut(&Attr) -> Option<T>>(
@@ -96,137 +401,237 @@ impl Game {
         player: &Player,
         f: F,
         default: T,
-    ) -> T {
-        self.get_attr(player, f).unwrap_or(default)
+    ) -> Result<T> {
+        Ok(self.get_attr(player, f)?.unwrap_or(default))
     }
 
     fn get_attr_sum<T: std::iter::Sum, F: FnMut(&Attr) -> Option<T>>(
         self: &Self,
         player: &Player,
         f: F,
-    ) -> T {
-        self.get_attrs(player).filter_map(f).sum()
+    ) -> Result<T> {
+        Ok(self.get_attrs(player)?.filter_map(f).sum())
     }
 
     /// Return a given player's attributes, most recent first.
-    fn get_attrs(self: &Self, player: &Player) -> std::iter::Rev<std::slice::Iter<Attr>> {
-        self.state
+    fn get_attrs(
+        self: &Self,
+        player: &Player,
+    ) -> Result<std::iter::Rev<std::slice::Iter<'_, Attr>>> {
+        Ok(self
+            .state
             .players
             .get(player)
-            .expect(&format!("No such player: {:?}", player))
+            .ok_or_else(|| Error::UnknownPlayer(player.clone()))?
             .iter()
-            .rev()
+            .rev())
     }
 
-    fn get_faction(self: &Self, player: &Player) -> Faction {
-        self.get_faction_and_rank(player).0
+    fn get_faction(self: &Self, player: &Player) -> Result<Faction> {
+        Ok(self.get_faction_and_rank(player)?.0)
     }
 
-    fn get_faction_and_rank(self: &Self, player: &Player) -> (Faction, i64) {
-        self.get_attrs(player)
+    fn get_faction_and_rank(self: &Self, player: &Player) -> Result<(Faction, i64)> {
+        self.get_attrs(player)?
             .find_map(|a| a.get_faction_and_rank())
-            .expect(&format!("Player does not have a faction: {:?}", player))
+            .ok_or_else(|| Error::MissingFaction(player.clone()))
     }
 
     fn get_faction_attr<T, F: FnMut(&Attr) -> Option<T>>(
         self: &Self,
         faction: &Faction,
         f: F,
-    ) -> Option<T> {
-        self.get_faction_attrs(faction).find_map(f)
+    ) -> Result<Option<T>> {
+        Ok(self.get_faction_attrs(faction)?.find_map(f))
     }
 
     /// Return a given player's attributes, most recent first.
-    fn get_faction_attrs(self: &Self, faction: &Faction) -> std::iter::Rev<std::slice::Iter<Attr>> {
-        self.state
+    fn get_faction_attrs(
+        self: &Self,
+        faction: &Faction,
+    ) -> Result<std::iter::Rev<std::slice::Iter<'_, Attr>>> {
+        Ok(self
+            .state
             .factions
             .get(faction)
-            .expect(&format!("No such faction: {:?}", faction))
+            .ok_or_else(|| Error::UnknownFaction(faction.clone()))?
             .iter()
-            .rev()
+            .rev())
     }
 
-    fn get_player_alignment(self: &Self, player: &Player) -> Alignment {
-        self.get_faction_alignment(&self.get_faction(player))
+    fn get_player_alignment(self: &Self, player: &Player) -> Result<Alignment> {
+        self.get_faction_alignment(&self.get_faction(player)?)
     }
 
-    fn get_faction_alignment(self: &Self, faction: &Faction) -> Alignment {
-        self.get_faction_attr(faction, |a| a.get_alignment())
-            .expect(&format!(
-                "Faction does not have an alignment: {:?}",
-                faction
-            ))
+    fn get_faction_alignment(self: &Self, faction: &Faction) -> Result<Alignment> {
+        self.get_faction_attr(faction, |a| a.get_alignment())?
+            .ok_or_else(|| Error::MissingAlignment(faction.clone()))
     }
 
-    fn get_faction_objective(self: &Self, faction: &Faction) -> Objective {
-        self.get_faction_attr(faction, |a| a.get_objective())
-            .expect(&format!(
-                "Faction does not have an objective: {:?}",
-                faction
-            ))
+    fn get_faction_objective(self: &Self, faction: &Faction) -> Result<Objective> {
+        self.get_faction_attr(faction, |a| a.get_objective())?
+            .ok_or_else(|| Error::MissingObjective(faction.clone()))
     }
 
-    fn get_faction_fate(self: &Self, faction: &Faction) -> Fate {
-        match &self.get_faction_objective(faction) {
+    /// Return a faction's fate.
+    fn get_faction_fate(self: &Self, faction: &Faction) -> Result<Fate> {
+        self.get_objective_fate(faction, &self.get_faction_objective(faction)?)
+    }
+
+    fn get_leader(self: &Self, faction: &Faction) -> Result<Option<Player>> {
+        Ok(self.get_members(faction)?.first().cloned())
+    }
+
+    fn get_living_players(self: &Self) -> Result<Vec<Player>> {
+        let mut players = Vec::new();
+        for player in self.state.players.keys() {
+            if self.is_alive(player)? {
+                players.push(player.clone());
+            }
+        }
+        Ok(players)
+    }
+
+    fn get_members(self: &Self, faction: &Faction) -> Result<Vec<Player>> {
+        let mut members_with_rank = Vec::new();
+        for player in self.get_living_players()? {
+            let (f, r) = self.get_faction_and_rank(&player)?;
+            if f == *faction {
+                members_with_rank.push((player, r));
+            }
+        }
+        members_with_rank.sort_by_key(|m| m.1);
+        Ok(members_with_rank.into_iter().map(|(p, _)| p).collect())
+    }
+
+    /// Return a faction's fate with respect to a given objective.
+    ///
+    /// Factions that eliminate others only win if someone of their alignment
+    /// survives to see it.
+    fn get_objective_fate(self: &Self, faction: &Faction, objective: &Objective) -> Result<Fate> {
+        let allies = self.num_living_alignment(&self.get_faction_alignment(faction)?)?;
+        Ok(match objective {
             Objective::Eliminate(alignment) => {
-                if self.num_living_alignment(alignment) == 0 {
+                if allies == 0 {
+                    Fate::Lost
+                } else if self.num_living_alignment(alignment)? == 0 {
                     Fate::Won
                 } else {
                     Fate::Losing
                 }
             }
-            Objective::EliminateFaction(faction) => {
-                if self.num_living_members(faction) == 0 {
+            Objective::EliminateFaction(target) => {
+                if allies == 0 {
+                    Fate::Lost
+                } else if self.num_living_members(target)? == 0 {
                     Fate::Won
                 } else {
                     Fate::Losing
                 }
             }
             Objective::AchieveMajority => {
-                if 2 * self.num_living_members(faction) > self.num_living_players() {
+                let members = self.num_living_members(faction)?;
+                if members == 0 {
+                    Fate::Lost
+                } else if 2 * members > self.num_living_players()? {
                     Fate::Won
                 } else {
                     Fate::Losing
                 }
             }
             Objective::Survive => {
-                if self.num_living_members(faction) == 0 {
+                if self.num_living_members(faction)? == 0 {
                     Fate::Lost
                 } else {
                     Fate::Winning
                 }
             }
-        }
-    }
-
-    fn get_leader(self: &Self, faction: &Faction) -> Player {
-        self.get_members(faction)[0].clone()
-    }
-
-    fn get_living_players(self: &Self) -> Vec<Player> {
-        self.state
-            .players
-            .keys()
-            .filter(|p| self.is_alive(p))
-            .map(|p| p.clone())
-            .collect()
-    }
-
-    fn get_members(self: &Self, faction: &Faction) -> Vec<Player> {
-        let mut members_with_rank: Vec<(Player, i64)> = self
-            .get_living_players()
-            .into_iter()
-            .filter_map(|p| {
-                let (f, r) = self.get_faction_and_rank(&p);
-                if f == *faction {
-                    Some((p, r))
+            Objective::All(objectives) => {
+                let mut fates = Vec::new();
+                for o in objectives {
+                    fates.push(self.get_objective_fate(faction, o)?);
+                }
+                if fates.contains(&Fate::Lost) {
+                    Fate::Lost
+                } else if fates.contains(&Fate::Losing) {
+                    Fate::Losing
+                } else if fates.contains(&Fate::Winning) {
+                    Fate::Winning
                 } else {
-                    None
+                    Fate::Won
                 }
-            })
-            .collect();
-        members_with_rank.sort_by_key(|m| m.1);
-        members_with_rank.into_iter().map(|(p, _)| p).collect()
+            }
+            Objective::Any(objectives) => {
+                let mut fates = Vec::new();
+                for o in objectives {
+                    fates.push(self.get_objective_fate(faction, o)?);
+                }
+                if fates.contains(&Fate::Won) {
+                    Fate::Won
+                } else if fates.contains(&Fate::Winning) {
+                    Fate::Winning
+                } else if fates.contains(&Fate::Losing) {
+                    Fate::Losing
+                } else {
+                    Fate::Lost
+                }
+            }
+            Objective::BeEliminatedByVote => {
+                let mut lynched = false;
+                for player in self.state.players.keys() {
+                    if self.get_faction(player)? == *faction {
+                        lynched |= self.is_lynched(player)?;
+                    }
+                }
+                if lynched {
+                    Fate::Winning
+                } else if self.num_living_members(faction)? == 0 {
+                    Fate::Lost
+                } else {
+                    Fate::Losing
+                }
+            }
+            Objective::Not(objective) => match self.get_objective_fate(faction, objective)? {
+                Fate::Losing => Fate::Winning,
+                Fate::Lost => Fate::Won,
+                Fate::Winning => Fate::Losing,
+                Fate::Won => Fate::Lost,
+            },
+            Objective::OutnumberFaction(target) => {
+                let members = self.num_living_members(faction)?;
+                if members == 0 {
+                    Fate::Lost
+                } else if members > self.num_living_members(target)? {
+                    Fate::Won
+                } else {
+                    Fate::Losing
+                }
+            }
+            Objective::SurviveWith(player) => {
+                if self.num_living_members(faction)? == 0 || !self.is_alive(player)? {
+                    Fate::Lost
+                } else {
+                    Fate::Winning
+                }
+            }
+            Objective::SeeEliminated(player) => {
+                if self.is_alive(player)? {
+                    Fate::Losing
+                } else {
+                    Fate::Winning
+                }
+            }
+            Objective::LastStanding => {
+                let members = self.num_living_members(faction)?;
+                if members == 0 {
+                    Fate::Lost
+                } else if members == self.num_living_players()? {
+                    Fate::Won
+                } else {
+                    Fate::Losing
+                }
+            }
+        })
     }
 
     /// Return inputs from the current phase.
@@ -247,75 +652,275 @@ impl Game {
         inputs
     }
 
+    /// Return the players protecting a given player, including their jailor.
+    fn get_protectors(self: &Self, player: &Player) -> Result<Vec<Player>> {
+        Ok(self
+            .get_attrs(player)?
+            .filter_map(|a| a.get_protector().or_else(|| a.get_jailor()))
+            .collect())
+    }
+
     fn get_rng(self: &mut Self) -> Rng {
         self.state.seed += 1;
         Rng::seed_from_u64(self.state.seed)
     }
 
-    fn get_status(self: &Self, player: &Player) -> PlayerStatus {
-        PlayerStatus::alive(self.is_alive(player))
+    /// Return what the given flip rule reveals about a player.
+    fn get_role_card(self: &Self, player: &Player, flip: &Flip) -> Result<RoleCard> {
+        let (alignment, faction, abilities) = match flip {
+            Flip::Hidden => (false, false, false),
+            Flip::Alignment => (true, false, false),
+            Flip::Faction => (true, true, false),
+            Flip::Role => (true, true, true),
+        };
+
+        Ok(RoleCard {
+            alignment: if alignment {
+                Some(self.get_player_alignment(player)?)
+            } else {
+                None
+            },
+            faction: if faction {
+                Some(self.get_faction(player)?)
+            } else {
+                None
+            },
+            abilities: if abilities {
+                Some(
+                    self.get_attrs(player)?
+                        .rev()
+                        .filter_map(|a| a.get_ability())
+                        .collect(),
+                )
+            } else {
+                None
+            },
+        })
+    }
+
+    fn get_status(self: &Self, player: &Player) -> Result<PlayerStatus> {
+        Ok(PlayerStatus::alive(self.is_alive(player)?))
+    }
+
+    /// Return the players who visited a given player this phase.
+    fn get_visitors(self: &Self, player: &Player) -> Result<Vec<Player>> {
+        let mut visitors = Vec::new();
+        for p in self.state.players.keys() {
+            if self.get_visits(p)?.contains(player) {
+                visitors.push(p.clone());
+            }
+        }
+        Ok(visitors)
+    }
+
+    /// Return the players a given player visited this phase.
+    fn get_visits(self: &Self, player: &Player) -> Result<Vec<Player>> {
+        let visits: Set<Player> = self
+            .get_attrs(player)?
+            .filter_map(|a| a.get_visited())
+            .collect();
+        Ok(visits.into_iter().collect())
+    }
+
+    /// Return the vote changes made this phase by players who are still
+    /// alive, along with their log index. Unvotes have no ballot.
+    fn get_vote_changes(self: &Self) -> Result<Vec<(usize, Player, Option<Ballot>)>> {
+        let start = self
+            .log
+            .iter()
+            .rposition(|(_, e)| matches!(e, Event::PhaseBegan(_)))
+            .unwrap_or(0);
+
+        let mut changes = Vec::new();
+        for (i, (_, event)) in self.log.iter().enumerate().skip(start) {
+            let (voter, ballot) = match event {
+                Event::Unvoted(voter) => (voter, None),
+                Event::VotedFor(voter, target) => (voter, Some(Ballot::Player(target.clone()))),
+                Event::VotedNoLynch(voter) => (voter, Some(Ballot::NoLynch)),
+                _ => continue,
+            };
+            if self.is_alive(voter)? {
+                changes.push((i, voter.clone(), ballot));
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Return who can see individual votes.
+    fn get_vote_visibility(self: &Self) -> Visibility {
+        if self.state.vote.secret {
+            Visibility::Moderator
+        } else {
+            Visibility::Public
+        }
+    }
+
+    /// Return how many votes a player's vote counts for.
+    fn get_vote_weight(self: &Self, player: &Player) -> Result<i64> {
+        let weight = self.get_attr_or(player, |a| a.get_vote_weight(), 1)?;
+        if self.is_revealed(player)? {
+            Ok(2 * weight)
+        } else {
+            Ok(weight)
+        }
+    }
+
+    /// Return who is currently voting for each ballot, in the order they voted.
+    fn get_wagons(self: &Self) -> Result<Map<Ballot, Vec<Player>>> {
+        let mut votes: Vec<(Player, Ballot)> = Vec::new();
+        for (_, voter, ballot) in self.get_vote_changes()? {
+            if votes
+                .iter()
+                .any(|(v, b)| *v == voter && Some(b) == ballot.as_ref())
+            {
+                continue;
+            }
+            votes.retain(|(v, _)| *v != voter);
+            if let Some(ballot) = ballot {
+                votes.push((voter, ballot));
+            }
+        }
+
+        let mut wagons: Map<Ballot, Vec<Player>> = Map::new();
+        for (voter, ballot) in votes {
+            wagons.entry(ballot).or_default().push(voter);
+        }
+        Ok(wagons)
     }
 
-    fn is_alive(self: &Self, player: &Player) -> bool {
+    fn is_alive(self: &Self, player: &Player) -> Result<bool> {
         self.get_attr_or(player, |a| a.is_alive(), true)
     }
 
-    fn is_bulletproof(self: &Self, player: &Player) -> bool {
+    fn is_bulletproof(self: &Self, player: &Player) -> Result<bool> {
         self.get_attr_or(player, |a| a.is_bulletproof(), false)
     }
 
-    fn kill(self: &mut Self, player: &Player) {
-        self.state.players.get_mut(player).unwrap().push(Attr::Dead);
-        self.log
-            .push((Visibility::Public, Event::Died(player.clone())));
+    fn is_doused(self: &Self, player: &Player) -> Result<bool> {
+        self.get_attr_or(player, |a| a.is_doused(), false)
     }
 
-    fn num_living_alignment(self: &Self, alignment: &Alignment) -> usize {
-        self.get_living_players()
-            .iter()
-            .filter(|p| self.get_player_alignment(p) == *alignment)
-            .count()
+    /// Return whether a ballot has reached a majority under majority rules.
+    fn is_hammered(self: &Self) -> Result<bool> {
+        if self.state.vote.threshold != Threshold::Majority {
+            return Ok(false);
+        }
+        let living = self.num_living_players()? as i64;
+        Ok(self.count_votes()?.values().any(|(v, _)| 2 * v > living))
+    }
+
+    fn is_lynched(self: &Self, player: &Player) -> Result<bool> {
+        self.get_attr_or(player, |a| a.is_lynched(), false)
+    }
+
+    fn is_poisoned(self: &Self, player: &Player) -> Result<bool> {
+        Ok(self.get_attr(player, |a| a.get_poisoned())?.is_some())
+    }
+
+    fn is_revealed(self: &Self, player: &Player) -> Result<bool> {
+        self.get_attr_or(player, |a| a.is_revealed(), false)
     }
 
-    fn num_living_members(self: &Self, faction: &Faction) -> usize {
-        self.get_members(faction).len()
+    fn is_unconvertible(self: &Self, player: &Player) -> Result<bool> {
+        self.get_attr_or(player, |a| a.is_unconvertible(), false)
     }
 
-    fn num_living_players(self: &Self) -> usize {
-        self.get_living_players().len()
+    fn is_voting(self: &Self, player: &Player) -> Result<bool> {
+        Ok(self.get_wagons()?.values().any(|w| w.contains(player)))
     }
 
-    fn num_votes_for(self: &Self, player: &Player) -> i64 {
+    /// Tell members of a visible faction who the other members are.
+    fn log_faction_members(self: &mut Self, faction: &Faction) -> Result<()> {
+        let membership = self
+            .get_faction_attr(faction, |a| a.get_membership())?
+            .unwrap_or(Membership::Hidden);
+        if membership != Membership::Visible {
+            return Ok(());
+        }
+
+        let members = self.get_members(faction)?;
+        for member in &members {
+            self.log.push((
+                Visibility::Player(member.clone()),
+                Event::FactionMembers(faction.clone(), members.clone()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn kill(self: &mut Self, player: &Player) -> Result<()> {
+        self.kill_with_event(player, Event::Died(player.clone()))
+    }
+
+    /// Kill a player, publicly logging the given death event.
+    ///
+    /// Linked players die of heartbreak. Only living players are taken with
+    /// them, so cyclic links don't recurse forever.
+    fn kill_with_event(self: &mut Self, player: &Player, event: Event) -> Result<()> {
+        self.add_attr(player, Attr::Dead)?;
+        self.log.push((Visibility::Public, event));
+
+        // Reveal the player's role.
+        if self.state.flip != Flip::Hidden {
+            let card = self.get_role_card(player, &self.state.flip)?;
+            self.log
+                .push((Visibility::Public, Event::Revealed(player.clone(), card)));
+        }
+
+        let linked: Vec<Player> = self
+            .get_attrs(player)?
+            .filter_map(|a| a.get_linked())
+            .collect();
+        for p in linked {
+            if self.is_alive(&p)? {
+                self.kill_with_event(&p, Event::DiedOfHeartbreak(p.clone()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn num_living_alignment(self: &Self, alignment: &Alignment) -> Result<usize> {
+        let mut n = 0;
+        for player in self.get_living_players()? {
+            if self.get_player_alignment(&player)? == *alignment {
+                n += 1;
+            }
+        }
+        Ok(n)
+    }
+
+    fn num_living_members(self: &Self, faction: &Faction) -> Result<usize> {
+        Ok(self.get_members(faction)?.len())
+    }
+
+    fn num_living_players(self: &Self) -> Result<usize> {
+        Ok(self.get_living_players()?.len())
+    }
+
+    fn num_votes_for(self: &Self, player: &Player) -> Result<i64> {
         self.get_attr_sum(player, |a| a.num_votes())
     }
 
     /// Resolve the current phase.
-    fn resolve(self: &mut Self) {
-        // Resolve actions.
-        let plan = self.resolve_build_plan();
+    fn resolve(self: &mut Self) -> Result<()> {
+        // Resolve actions. Players only visit each other at night.
+        let plan = self.resolve_build_plan()?;
+        let night = matches!(self.phase, Phase::Night(_));
+        if night {
+            self.resolve_visits(&plan)?;
+        }
         for (player, action) in &plan {
-            self.resolve_action(player, action);
+            self.resolve_action(player, action)?;
+            if night {
+                self.resolve_triggers(player, action)?;
+            }
         }
 
         // Resolve elimination.
         if let Phase::Day(_) = self.phase {
-            let queue = self.get_living_players();
-            let mut rng = self.get_rng();
-
-            // Count votes.
-            let mut queue: Vec<(i64, &Player)> =
-                queue.iter().map(|p| (self.num_votes_for(p), p)).collect();
-
-            // Sort the queue by decreasing vote count, randomizing ties.
-            queue.shuffle(&mut rng);
-            queue.sort_by_cached_key(|(v, _)| -v);
-
-            // Kill the first player if they received positive votes.
-            if let Some((votes, player)) = queue.first() {
-                if *votes > 0 {
-                    self.kill(player);
-                }
-            }
+            self.resolve_elimination()?;
         }
 
         // Expire effects.
@@ -326,56 +931,370 @@ impl Game {
             *attrs = attrs.iter().filter_map(|m| m.next_phase()).collect();
         }
 
-        // Evaluate win conditions.
-        for (faction, _) in &self.state.factions {
-            if self.get_faction_fate(faction) == Fate::Won {
-                self.log
-                    .push((Visibility::Public, Event::Won(faction.clone())));
+        // Resolve poison.
+        for player in self.get_living_players()? {
+            if self.get_attr(&player, |a| a.get_poisoned())? == Some(0) {
+                self.kill_with_event(&player, Event::DiedOfPoison(player.clone()))?;
             }
         }
 
-        // Advance phase.
+        // Evaluate win conditions. The game ends once a faction has won or
+        // no faction is still losing, i.e. nobody is left to change the
+        // outcome. Every faction that's winning at that point wins.
+        let mut fates = Vec::new();
+        for faction in self.state.factions.keys() {
+            fates.push((faction.clone(), self.get_faction_fate(faction)?));
+        }
+        let over = fates.iter().any(|(_, fate)| *fate == Fate::Won)
+            || fates.iter().all(|(_, fate)| *fate != Fate::Losing);
+        if over {
+            let winners: Vec<Faction> = fates
+                .into_iter()
+                .filter(|(_, fate)| *fate == Fate::Won || *fate == Fate::Winning)
+                .map(|(faction, _)| faction)
+                .collect();
+            if winners.is_empty() {
+                self.log.push((Visibility::Public, Event::Draw));
+            }
+            for faction in winners {
+                self.log.push((Visibility::Public, Event::Won(faction)));
+            }
+        }
+
+        // Advance phase, unless the game is over.
         self.log
             .push((Visibility::Public, Event::PhaseEnded(self.phase.clone())));
-        self.phase = self.phase.next();
-        self.log
-            .push((Visibility::Public, Event::PhaseBegan(self.phase.clone())));
+        if over {
+            self.log.push((Visibility::Public, Event::GameOver));
+            self.over = true;
+        } else {
+            self.phase = self.phase.next();
+            self.log
+                .push((Visibility::Public, Event::PhaseBegan(self.phase.clone())));
+        }
+
+        Ok(())
     }
 
     /// Resolve the effects of a single action.
-    fn resolve_action(self: &mut Self, player: &Player, action: &Action) {
+    fn resolve_action(self: &mut Self, player: &Player, action: &Action) -> Result<()> {
         match action {
+            Action::Alert => {
+                let trigger = Attr::OnVisited(Box::new(Action::Kill(VISITOR.to_string())));
+                self.add_attr(player, Attr::Phases(1, Box::new(Attr::Bulletproof)))?;
+                self.add_attr(player, Attr::Phases(1, Box::new(trigger)))?;
+            }
+            Action::Cure(target) => {
+                self.state
+                    .players
+                    .get_mut(target)
+                    .ok_or_else(|| Error::UnknownPlayer(target.clone()))?
+                    .retain(|a| a.get_poisoned().is_none());
+            }
+            Action::Reveal => {
+                if !self.is_revealed(player)? {
+                    self.add_attr(player, Attr::Revealed)?;
+                    let card = self.get_role_card(player, &Flip::Role)?;
+                    self.log
+                        .push((Visibility::Public, Event::Revealed(player.clone(), card)));
+                }
+            }
+            Action::Disenfranchise(target) => {
+                // Lasts through the end of the following day.
+                self.add_attr(target, Attr::Phases(2, Box::new(Attr::Disenfranchised)))?;
+                self.log
+                    .push((Visibility::Public, Event::Disenfranchised(target.clone())));
+            }
+            Action::Disguise(target) => {
+                let disguise = Attr::DetectsAs(Alignment::Good);
+                self.add_attr(target, Attr::Phases(1, Box::new(disguise)))?;
+            }
+            Action::Execute(target) => {
+                // Check again, in case the execution was retargeted.
+                if self.is_alive(target)? && self.get_jailor(target)?.as_ref() == Some(player) {
+                    self.kill(target)?;
+                }
+            }
+            Action::Frame(target) => {
+                let frame = Attr::DetectsAs(Alignment::Evil);
+                self.add_attr(target, Attr::Phases(1, Box::new(frame)))?;
+            }
+            Action::Guard(target) => {
+                self.add_attr(
+                    target,
+                    Attr::Phases(1, Box::new(Attr::Guarded(player.clone()))),
+                )?;
+            }
             Action::Kill(target) => {
-                if self.is_alive(target) && !self.is_bulletproof(target) {
-                    self.kill(target);
+                self.resolve_kill(player, target)?;
+            }
+            Action::Douse(target) => {
+                if !self.is_doused(target)? {
+                    self.add_attr(target, Attr::Doused)?;
+                }
+            }
+            Action::Ignite => {
+                for target in self.get_living_players()? {
+                    if self.is_doused(&target)? {
+                        self.resolve_kill(player, &target)?;
+                    }
                 }
             }
             Action::Investigate(target) => {
-                let result = self.get_player_alignment(target);
+                let detected = match self.get_attr(target, |a| a.get_detects_as())? {
+                    Some(alignment) => alignment,
+                    None => self.get_player_alignment(target)?,
+                };
+                let result = match self.get_attr_or(player, |a| a.get_sanity(), Sanity::Sane)? {
+                    Sanity::Sane => detected,
+                    Sanity::Naive => Alignment::Good,
+                    Sanity::Paranoid => Alignment::Evil,
+                    Sanity::Insane => match detected {
+                        Alignment::Evil => Alignment::Good,
+                        Alignment::Good => Alignment::Evil,
+                        Alignment::Neutral => Alignment::Neutral,
+                    },
+                };
                 self.log.push((
                     Visibility::Player(player.clone()),
                     Event::FoundAlignment(target.clone(), result),
                 ));
             }
-            Action::Order(minion, faction_action) => self.resolve_action(minion, faction_action),
+            Action::InvestigateFaction(target) => {
+                let result = self.get_faction(target)?;
+                self.log.push((
+                    Visibility::Player(player.clone()),
+                    Event::FoundFaction(target.clone(), result),
+                ));
+            }
+            Action::InvestigateRole(target) => {
+                let result = self.get_role_card(target, &Flip::Role)?;
+                self.log.push((
+                    Visibility::Player(player.clone()),
+                    Event::FoundRole(target.clone(), result),
+                ));
+            }
+            Action::Jail(target) => {
+                // Lasts through the end of the following night.
+                self.add_attr(
+                    target,
+                    Attr::Phases(2, Box::new(Attr::Jailed(player.clone()))),
+                )?;
+                self.log.push((
+                    Visibility::Player(target.clone()),
+                    Event::Jailed(target.clone()),
+                ));
+            }
+            Action::Link(a, b) => {
+                if a != b {
+                    self.add_attr(a, Attr::LinkedTo(b.clone()))?;
+                    self.add_attr(b, Attr::LinkedTo(a.clone()))?;
+                    for p in [a, b].iter() {
+                        self.log.push((
+                            Visibility::Player((*p).clone()),
+                            Event::Linked(a.clone(), b.clone()),
+                        ));
+                    }
+                }
+            }
+            Action::Order(minion, faction_action) => self.resolve_action(minion, faction_action)?,
+            Action::Poison(target) => {
+                if self.is_alive(target)?
+                    && !self.is_bulletproof(target)?
+                    && self.get_protectors(target)?.is_empty()
+                    && !self.is_poisoned(target)?
+                {
+                    self.add_attr(target, Attr::Poisoned(POISON_PHASES))?;
+                    self.log.push((
+                        Visibility::Player(target.clone()),
+                        Event::Poisoned(target.clone()),
+                    ));
+                }
+            }
             Action::Protect(target) => {
-                self.add_attr(target, Attr::Phases(1, Box::new(Attr::Bulletproof)));
+                self.add_attr(
+                    target,
+                    Attr::Phases(1, Box::new(Attr::Protected(player.clone()))),
+                )?;
+            }
+            Action::Recruit(target) => {
+                let faction = self.get_faction(player)?;
+                let old_faction = self.get_faction(target)?;
+                if self.is_alive(target)?
+                    && !self.is_unconvertible(target)?
+                    && old_faction != faction
+                {
+                    // Recruits join at the bottom of the chain of command.
+                    let mut rank = 0;
+                    for member in self.get_members(&faction)? {
+                        rank = rank.max(self.get_faction_and_rank(&member)?.1 + 1);
+                    }
+                    self.add_attr(target, Attr::Member(faction.clone(), rank))?;
+                    self.log.push((
+                        Visibility::Player(target.clone()),
+                        Event::Converted(target.clone(), faction.clone()),
+                    ));
+                    self.log_faction_members(&old_faction)?;
+                    self.log_faction_members(&faction)?;
+                }
+            }
+            Action::Silence(target) => {
+                // Lasts through the end of the following day.
+                self.add_attr(target, Attr::Phases(2, Box::new(Attr::Silenced)))?;
+                self.log
+                    .push((Visibility::Public, Event::Silenced(target.clone())));
+            }
+            Action::Track(target) => {
+                let result = self.get_visits(target)?;
+                self.log.push((
+                    Visibility::Player(player.clone()),
+                    Event::FoundVisits(target.clone(), result),
+                ));
             }
-            Action::Vote(target) => {
-                self.add_attr(target, Attr::Phases(1, Box::new(Attr::ReceivedVotes(1))));
+            Action::Watch(target) => {
+                let result = self
+                    .get_visitors(target)?
+                    .into_iter()
+                    .filter(|p| p != player)
+                    .collect();
+                self.log.push((
+                    Visibility::Player(player.clone()),
+                    Event::FoundVisitors(target.clone(), result),
+                ));
             }
             _ => {}
         }
+
+        Ok(())
+    }
+
+    /// Resolve an attack on a player.
+    ///
+    /// The most recent living bodyguard dies in the target's place, taking the
+    /// attacker with them. Otherwise the target dies unless they are
+    /// bulletproof or protected.
+    fn resolve_kill(self: &mut Self, attacker: &Player, target: &Player) -> Result<()> {
+        if !self.is_alive(target)? {
+            return Ok(());
+        }
+
+        let guards: Vec<Player> = self
+            .get_attrs(target)?
+            .filter_map(|a| a.get_guard())
+            .collect();
+        for guard in guards {
+            if self.is_alive(&guard)? {
+                self.kill(&guard)?;
+                if self.is_alive(attacker)? && !self.is_bulletproof(attacker)? {
+                    self.kill(attacker)?;
+                }
+                return Ok(());
+            }
+        }
+
+        if self.is_bulletproof(target)? {
+            return Ok(());
+        }
+
+        let protectors = self.get_protectors(target)?;
+        if protectors.is_empty() {
+            return self.kill(target);
+        }
+        if self.state.report_saves {
+            for protector in protectors {
+                self.log
+                    .push((Visibility::Player(protector), Event::Saved(target.clone())));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Eliminate the player or players voted out today.
+    fn resolve_elimination(self: &mut Self) -> Result<()> {
+        let players = self.get_living_players()?;
+        let counts = self.count_votes()?;
+        let mut rng = self.get_rng();
+
+        // Publish the final tally of a secret vote.
+        if self.state.vote.secret {
+            let totals = counts
+                .iter()
+                .filter(|(_, (v, _))| *v != 0)
+                .map(|(b, (v, _))| (b.clone(), *v))
+                .collect();
+            self.log
+                .push((Visibility::Public, Event::VoteTotals(totals)));
+        }
+
+        // Queue up every living player, plus no lynch if anyone voted for it.
+        let mut queue: Vec<_> = players.iter().map(|p| Ballot::Player(p.clone())).collect();
+        if counts.contains_key(&Ballot::NoLynch) {
+            queue.push(Ballot::NoLynch);
+        }
+        let mut queue: Vec<_> = queue
+            .into_iter()
+            .map(|b| {
+                let (votes, led) = counts.get(&b).cloned().unwrap_or_default();
+                (votes, led, b)
+            })
+            .collect();
+
+        // Sort the queue by decreasing vote count, randomizing ties.
+        queue.shuffle(&mut rng);
+        queue.sort_by_cached_key(|(v, _, _)| -v);
+
+        // Nobody is eliminated without positive votes or a required majority.
+        let votes = match queue.first() {
+            Some((v, _, _)) => *v,
+            None => return Ok(()),
+        };
+        if votes <= 0 {
+            return Ok(());
+        }
+        if self.state.vote.threshold == Threshold::Majority && 2 * votes <= players.len() as i64 {
+            return Ok(());
+        }
+
+        // Break ties.
+        let tied: Vec<_> = queue
+            .into_iter()
+            .take_while(|(v, _, _)| *v == votes)
+            .collect();
+        let eliminated = match self.state.vote.ties {
+            Ties::Random => tied.into_iter().take(1).collect(),
+            Ties::NoElimination if tied.len() > 1 => Vec::new(),
+            Ties::NoElimination => tied,
+            Ties::EliminateAll => tied,
+            Ties::PreviousLeader => tied
+                .into_iter()
+                .min_by_key(|(_, led, _)| *led)
+                .into_iter()
+                .collect(),
+        };
+
+        for (_, _, ballot) in eliminated {
+            if let Ballot::Player(player) = ballot {
+                self.add_attr(&player, Attr::Lynched)?;
+                self.kill(&player)?;
+            }
+        }
+
+        Ok(())
     }
 
     /// Build a plan of the actions to resolve and the order to resolve them in.
-    fn resolve_build_plan(self: &mut Self) -> Plan {
+    fn resolve_build_plan(self: &mut Self) -> Result<Plan> {
         // Get valid actions.
         let mut plan = Plan::new();
         let mut log = Log::new();
         for input in self.get_phase_inputs().iter().rev() {
             match input {
                 Input::Use(player, action) => {
+                    // Immediate actions were already resolved when submitted.
+                    if let Action::Immediate(_) = action.performer(player).1 {
+                        continue;
+                    }
                     if let Some(a) = self.resolve_get_action(player, action) {
                         plan.push((player.clone(), action.clone()));
                         *a = a.tap();
@@ -402,45 +1321,111 @@ impl Game {
         log.reverse();
         self.log.append(&mut log);
 
-        plan
+        // Drop actions performed by blocked players. Prisoners start blocked.
+        let mut blocked = Set::new();
+        for player in self.state.players.keys() {
+            if self.get_jailor(player)?.is_some() {
+                blocked.insert(player.clone());
+            }
+        }
+        let mut unblocked = Plan::new();
+        for (player, action) in plan {
+            let (performer, performed) = action.performer(&player);
+            if blocked.contains(performer) {
+                self.log.push((
+                    Visibility::Player(performer.clone()),
+                    Event::Blocked(player.clone(), action.clone()),
+                ));
+                continue;
+            }
+            if let Action::Block(target) = performed {
+                blocked.insert(target.clone());
+            }
+            unblocked.push((player, action));
+        }
+
+        // Rewrite the targets of actions following redirects and swaps.
+        let mut plan = unblocked;
+        for i in 0..plan.len() {
+            let performed = plan[i].1.performer(&plan[i].0).1.clone();
+            for (player, action) in plan.iter_mut().skip(i + 1) {
+                let retargeted = match &performed {
+                    Action::Redirect(from, to) if action.performer(player).0 == from => {
+                        action.retarget(&|_| to.clone())
+                    }
+                    Action::Swap(a, b) => action.retarget(&|p| {
+                        if p == a {
+                            b.clone()
+                        } else if p == b {
+                            a.clone()
+                        } else {
+                            p.clone()
+                        }
+                    }),
+                    _ => continue,
+                };
+                if retargeted != *action {
+                    self.log.push((
+                        Visibility::Moderator,
+                        Event::Retargeted(player.clone(), action.clone(), retargeted.clone()),
+                    ));
+                    *action = retargeted;
+                }
+            }
+        }
+
+        Ok(plan)
     }
 
-    /// Return the first placeholder action matching the given action, if any.
+    /// Return the attribute allowing a player to use an action, if any.
     fn resolve_get_action(self: &mut Self, player: &Player, action: &Action) -> Option<&mut Attr> {
-        // Check faction actions.
-        let mut factions = Map::new();
-        for (faction, _) in &self.state.factions {
-            if self.get_leader(&faction) == *player {
-                factions.insert(
-                    faction.clone(),
-                    Set::from_iter(self.get_members(faction).into_iter()),
-                );
-            }
-        }
-        for (faction, attrs) in &mut self.state.factions {
-            for attr in attrs.iter_mut() {
-                if factions.contains_key(faction)
-                    && attr.allows_action(&self.phase, player, &factions[faction], action)
-                {
-                    return Some(attr);
-                }
+        match self.check_action(player, action).ok()? {
+            ActionSource::Faction(faction, i) => self.state.factions.get_mut(&faction)?.get_mut(i),
+            ActionSource::Player(i) => self.state.players.get_mut(player)?.get_mut(i),
+        }
+    }
+
+    /// Resolve the actions triggered by the visits a planned action makes.
+    ///
+    /// Triggered actions don't visit, so they can't set off further triggers.
+    fn resolve_triggers(self: &mut Self, player: &Player, action: &Action) -> Result<()> {
+        let (performer, performed) = action.performer(player);
+        for target in performed.visits() {
+            if target == *performer {
+                continue;
+            }
+            let triggers: Vec<Action> = self
+                .get_attrs(&target)?
+                .filter_map(|a| a.get_on_visited())
+                .collect();
+            for trigger in triggers {
+                let triggered = trigger.retarget(&|p| {
+                    if p == VISITOR {
+                        performer.clone()
+                    } else {
+                        p.clone()
+                    }
+                });
+                self.log.push((
+                    Visibility::Moderator,
+                    Event::Triggered(target.clone(), triggered.clone()),
+                ));
+                self.resolve_action(&target, &triggered)?;
             }
         }
 
-        // Check individual actions.
-        for attr in self
-            .state
-            .players
-            .get_mut(player)
-            .expect(&format!("No such player: {:?}", player))
-        {
-            if let Some(a) = attr.get_action() {
-                if a.matches(&self.phase, player, &Set::new(), action) {
-                    return Some(attr);
-                }
+        Ok(())
+    }
+
+    /// Record who visited whom for the planned actions.
+    fn resolve_visits(self: &mut Self, plan: &Plan) -> Result<()> {
+        for (player, action) in plan {
+            let (performer, performed) = action.performer(player);
+            for target in performed.visits() {
+                self.add_attr(performer, Attr::Phases(1, Box::new(Attr::Visited(target))))?;
             }
         }
 
-        None
+        Ok(())
     }
 }
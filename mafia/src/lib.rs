@@ -6,9 +6,11 @@
 mod action;
 mod alignment;
 mod attr;
+mod error;
 mod event;
 mod faction;
 mod fate;
+mod flip;
 mod game;
 mod input;
 mod log;
@@ -16,16 +18,21 @@ mod membership;
 mod objective;
 mod phase;
 mod player;
+mod role;
+mod sanity;
 mod state;
 mod util;
 mod visibility;
+mod vote;
 
 pub use crate::action::*;
 pub use crate::alignment::*;
 pub use crate::attr::*;
+pub use crate::error::*;
 pub use crate::event::*;
 pub use crate::faction::*;
 pub use crate::fate::*;
+pub use crate::flip::*;
 pub use crate::game::*;
 pub use crate::input::*;
 pub use crate::log::*;
@@ -33,6 +40,9 @@ pub use crate::membership::*;
 pub use crate::objective::*;
 pub use crate::phase::*;
 pub use crate::player::*;
+pub use crate::role::*;
+pub use crate::sanity::*;
 pub use crate::state::*;
 pub use crate::util::*;
 pub use crate::visibility::*;
+pub use crate::vote::*;
@@ -16,4 +16,28 @@ pub enum Objective {
 
     /// Survive until the end of the game.
     Survive,
+
+    /// Survive until the end of the game along with a given player.
+    SurviveWith(Player),
+
+    /// Achieve every one of the given objectives.
+    All(Vec<Objective>),
+
+    /// Achieve at least one of the given objectives.
+    Any(Vec<Objective>),
+
+    /// Be the only faction with surviving members.
+    LastStanding,
+
+    /// Have a member eliminated by vote. Doesn't end the game.
+    BeEliminatedByVote,
+
+    /// Fail the given objective.
+    Not(Box<Objective>),
+
+    /// Have more surviving members than a given faction.
+    OutnumberFaction(Faction),
+
+    /// See a given player die. Doesn't end the game.
+    SeeEliminated(Player),
 }
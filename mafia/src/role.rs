@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::action::*;
+use crate::alignment::*;
+use crate::util::*;
+
+/// Information revealed about a player's role.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+pub struct RoleCard {
+    /// Player's alignment.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub alignment: Option<Alignment>,
+
+    /// Player's faction.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub faction: Option<Faction>,
+
+    /// Player's individual actions.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub abilities: Option<Vec<Action>>,
+}
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// How an investigator's alignment results are skewed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Sanity {
+    /// Results are accurate.
+    #[default]
+    Sane,
+
+    /// Everyone appears good.
+    Naive,
+
+    /// Everyone appears evil.
+    Paranoid,
+
+    /// Good and evil are swapped. Neutral players still appear neutral.
+    Insane,
+}
@@ -1,13 +1,40 @@
 use serde::{Deserialize, Serialize};
 
+use crate::action::*;
+use crate::error::*;
 use crate::faction::*;
+use crate::flip::*;
+use crate::objective::*;
 use crate::player::*;
+use crate::util::*;
+use crate::vote::*;
+
+/// Placeholders usable in individual actions.
+const PLAYER_PLACEHOLDERS: &[&str] = &["$OTHER_PLAYER", "$PLAYER"];
+
+/// Placeholders usable in faction actions.
+const FACTION_PLACEHOLDERS: &[&str] = &["$MEMBER", "$OTHER_PLAYER", "$PLAYER"];
+
+/// Placeholders usable in actions triggered by a visit.
+const TRIGGER_PLACEHOLDERS: &[&str] = &["$VISITOR"];
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 pub struct State {
     pub factions: Factions,
     pub players: Players,
     pub seed: u64,
+
+    /// What is revealed about players when they die.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub flip: Flip,
+
+    /// Whether protectors are told when they save someone.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub report_saves: bool,
+
+    /// Rules for the day vote.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub vote: VoteRules,
 }
 
 impl State {
@@ -16,6 +43,106 @@ impl State {
             factions: Factions::new(),
             players: Players::new(),
             seed: 0,
+            flip: Flip::default(),
+            report_saves: false,
+            vote: VoteRules::default(),
+        }
+    }
+
+    /// Check that the state is a valid game setup, returning all problems found.
+    pub fn validate(self: &Self) -> std::result::Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+
+        for (faction, attrs) in &self.factions {
+            if !attrs.iter().any(|a| a.get_alignment().is_some()) {
+                errors.push(Error::MissingAlignment(faction.clone()));
+            }
+            if !attrs.iter().any(|a| a.get_objective().is_some()) {
+                errors.push(Error::MissingObjective(faction.clone()));
+            }
+            for objective in attrs.iter().filter_map(|a| a.get_objective()) {
+                self.validate_objective(faction, &objective, &mut errors);
+            }
+            for action in attrs.iter().filter_map(|a| a.get_action()) {
+                self.validate_action(faction, &action, FACTION_PLACEHOLDERS, &mut errors);
+            }
+        }
+
+        for (player, attrs) in &self.players {
+            let factions: Vec<_> = attrs
+                .iter()
+                .filter_map(|a| a.get_faction_and_rank())
+                .collect();
+            match factions.as_slice() {
+                [] => errors.push(Error::MissingFaction(player.clone())),
+                [(faction, _)] => {
+                    if !self.factions.contains_key(faction) {
+                        errors.push(Error::UnknownFaction(faction.clone()));
+                    }
+                }
+                _ => errors.push(Error::MultipleFactions(player.clone())),
+            }
+            for action in attrs.iter().filter_map(|a| a.get_action()) {
+                self.validate_action(player, &action, PLAYER_PLACEHOLDERS, &mut errors);
+            }
+            for action in attrs.iter().filter_map(|a| a.get_on_visited()) {
+                self.validate_action(player, &action, TRIGGER_PLACEHOLDERS, &mut errors);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check that a player or faction's action only refers to known players and
+    /// placeholders.
+    fn validate_action(
+        self: &Self,
+        owner: &PlayerOrFaction,
+        action: &Action,
+        placeholders: &[&str],
+        errors: &mut Vec<Error>,
+    ) {
+        for target in action.targets() {
+            if target.starts_with('$') {
+                if !placeholders.contains(&target.as_str()) {
+                    errors.push(Error::UnknownPlaceholder(owner.clone(), target));
+                }
+            } else if !self.players.contains_key(&target) {
+                errors.push(Error::UnknownTarget(owner.clone(), target));
+            }
+        }
+    }
+
+    /// Check that a faction's objective only refers to known factions and
+    /// players.
+    fn validate_objective(
+        self: &Self,
+        faction: &Faction,
+        objective: &Objective,
+        errors: &mut Vec<Error>,
+    ) {
+        match objective {
+            Objective::All(objectives) | Objective::Any(objectives) => {
+                for o in objectives {
+                    self.validate_objective(faction, o, errors);
+                }
+            }
+            Objective::EliminateFaction(target) | Objective::OutnumberFaction(target) => {
+                if !self.factions.contains_key(target) {
+                    errors.push(Error::UnknownFaction(target.clone()));
+                }
+            }
+            Objective::Not(o) => self.validate_objective(faction, o, errors),
+            Objective::SeeEliminated(player) | Objective::SurviveWith(player) => {
+                if !self.players.contains_key(player) {
+                    errors.push(Error::UnknownTarget(faction.clone(), player.clone()));
+                }
+            }
+            _ => {}
         }
     }
 }
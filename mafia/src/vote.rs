@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::*;
+
+/// Something a player can vote for during the day.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Ballot {
+    /// Eliminate nobody.
+    NoLynch,
+
+    /// Eliminate the given player.
+    Player(Player),
+}
+
+/// How many votes it takes to eliminate a player.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Threshold {
+    /// More votes than anyone else, counted at the end of the day.
+    #[default]
+    Plurality,
+
+    /// More than half of the living players. Reaching a majority immediately
+    /// ends the day.
+    Majority,
+}
+
+/// Who is eliminated when several players are tied for the most votes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Ties {
+    /// A random tied player.
+    #[default]
+    Random,
+
+    /// Nobody.
+    NoElimination,
+
+    /// Every tied player.
+    EliminateAll,
+
+    /// The tied player who reached their vote count first.
+    PreviousLeader,
+}
+
+/// Rules for the day vote.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct VoteRules {
+    /// How many votes it takes to eliminate a player.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub threshold: Threshold,
+
+    /// Who is eliminated in a tie.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub ties: Ties,
+
+    /// Whether votes are secret, with only the final tally made public.
+    #[serde(default, skip_serializing_if = "IsDefault::is_default")]
+    pub secret: bool,
+}
@@ -40,29 +40,44 @@ impl TestHelper {
 pub fn run_test<P: AsRef<std::path::Path>>(path: P) {
     let mut t = TestHelper::new(path);
 
-    let mut game = mafia::Game::new_from_state(t.load("in.setup.ron"));
+    let setup: mafia::State = t.load("in.setup.ron");
+    if let Err(errors) = setup.validate() {
+        t.save("out.setup_errors.ron", &errors);
+        return;
+    }
+
+    let mut game = mafia::Game::new_from_state(setup);
     let inputs: mafia::Inputs = t.load("in.actions.ron");
 
+    let mut errors = Vec::new();
     let mut log_start = 0;
     for input in inputs {
-        game.apply(&input);
-        match input {
-            mafia::Input::EndPhase => {
-                t.save(
-                    format!(
-                        "out.{}.{}_log.ron",
-                        game.phase.prev().num(),
-                        game.phase.prev().kind_str(),
-                    ),
-                    &game.log[log_start..game.log.len() - 1].to_vec(),
-                );
-                t.save(
-                    format!("out.{}.{}.ron", game.phase.num(), game.phase.kind_str(),),
-                    &game.state,
-                );
-                log_start = game.log.len() - 1;
-            }
-            _ => {}
+        let phase = game.phase.clone();
+        if let Err(e) = game.try_apply(&input) {
+            errors.push((input, e));
+            continue;
+        }
+
+        if game.is_over() {
+            t.save(
+                format!("out.{}.{}_log.ron", phase.num(), phase.kind_str()),
+                &game.log[log_start..].to_vec(),
+            );
+            t.save("out.end.ron", &game.state);
+        } else if game.phase != phase {
+            t.save(
+                format!("out.{}.{}_log.ron", phase.num(), phase.kind_str()),
+                &game.log[log_start..game.log.len() - 1].to_vec(),
+            );
+            t.save(
+                format!("out.{}.{}.ron", game.phase.num(), game.phase.kind_str()),
+                &game.state,
+            );
+            log_start = game.log.len() - 1;
         }
     }
+
+    if !errors.is_empty() {
+        t.save("out.errors.ron", &errors);
+    }
 }